@@ -0,0 +1,330 @@
+//! Background animations that can be paused, swapped and stopped.
+//!
+//! An `Animator` moves a `BlinkStick` onto a thread that renders an effect
+//! into a frame buffer at a fixed rate and sends it to one channel. The
+//! returned `AnimationHandle` controls the thread and gives the device back
+//! once the animation is stopped.
+
+use crate::{
+    BlinkStick, BlinkStickError, Effect, Engine, Result, RgbColor, Transport, UsbTransport,
+};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// An effect that can be moved onto the animation thread
+///
+/// Effects are rendered with the time since they started, excluding paused
+/// periods.
+pub type BoxedEffect = Box<dyn Effect + Send>;
+
+/// What the LEDs show once an animation is stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EndState {
+    /// Switch every animated LED off
+    #[default]
+    Off,
+    /// Keep the last rendered frame
+    Hold,
+    /// Fill every animated LED with a color
+    Color(RgbColor),
+}
+
+/// Runs effects on a background thread
+pub struct Animator<T: Transport + Send + 'static = UsbTransport> {
+    device: BlinkStick<T>,
+    engine: Engine,
+    end_state: EndState,
+}
+
+impl<T: Transport + Send + 'static> Animator<T> {
+    /// Animate channel 0 with the LED count of the device model
+    pub fn new(device: BlinkStick<T>) -> Self {
+        Self {
+            engine: Engine::for_device(&device),
+            device,
+            end_state: EndState::Off,
+        }
+    }
+
+    /// Channel the effect is sent to
+    pub fn channel(mut self, channel: u8) -> Self {
+        self.engine = self.engine.channel(channel);
+        self
+    }
+
+    /// Number of LEDs in the frame handed to the effect
+    pub fn led_count(mut self, led_count: u8) -> Self {
+        self.engine = self.engine.led_count(led_count);
+        self
+    }
+
+    /// Target frames per second
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.engine = self.engine.fps(fps);
+        self
+    }
+
+    /// Time between two frames
+    pub fn frame_interval(mut self, frame_interval: Duration) -> Self {
+        self.engine = self.engine.frame_interval(frame_interval);
+        self
+    }
+
+    /// What the LEDs show after `stop`
+    pub fn end_state(mut self, end_state: EndState) -> Self {
+        self.end_state = end_state;
+        self
+    }
+
+    /// Start rendering `effect` on a background thread
+    pub fn start<E>(self, effect: E) -> AnimationHandle<T>
+    where
+        E: Effect + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            control: Mutex::new(Control {
+                state: RunState::Running,
+                replacement: None,
+                error: None,
+            }),
+            changed: Condvar::new(),
+        });
+        let thread_shared = shared.clone();
+
+        let thread = thread::spawn(move || self.run(Box::new(effect), &thread_shared));
+
+        AnimationHandle {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    fn run(self, mut effect: BoxedEffect, shared: &Shared) -> Result<BlinkStick<T>, StopError<T>> {
+        let mut frame = self.engine.blank_frame();
+        let mut elapsed = Duration::ZERO;
+        let mut last_tick = Instant::now();
+
+        loop {
+            {
+                let mut control = shared.control();
+
+                while control.state == RunState::Paused {
+                    control = shared.wait(control);
+                    last_tick = Instant::now();
+                }
+
+                if control.state == RunState::Stopping {
+                    break;
+                }
+
+                let now = Instant::now();
+                elapsed += now - last_tick;
+                last_tick = now;
+
+                if let Some(next) = control.replacement.take() {
+                    effect = next;
+                    elapsed = Duration::ZERO;
+                }
+            }
+
+            effect.render(elapsed, &mut frame);
+
+            // A failed frame is skipped; the next one tries again
+            if let Err(err) = self.device.set_colors(self.engine.channel, &frame) {
+                shared.control().error = Some(err);
+            }
+
+            let control = shared.control();
+            let _ =
+                shared
+                    .changed
+                    .wait_timeout_while(control, self.engine.frame_interval, |control| {
+                        control.state == RunState::Running && control.replacement.is_none()
+                    });
+        }
+
+        let ended = match &self.end_state {
+            EndState::Off => self.fill(&RgbColor::default()),
+            EndState::Hold => Ok(()),
+            EndState::Color(color) => self.fill(color),
+        };
+
+        match ended {
+            Ok(()) => Ok(self.device),
+            Err(error) => Err(StopError {
+                error,
+                device: Some(Box::new(self.device)),
+            }),
+        }
+    }
+
+    fn fill(&self, color: &RgbColor) -> Result<()> {
+        self.device.set_colors(
+            self.engine.channel,
+            &vec![color.clone(); self.engine.led_count],
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunState {
+    Running,
+    Paused,
+    Stopping,
+}
+
+struct Control {
+    state: RunState,
+    replacement: Option<BoxedEffect>,
+    error: Option<BlinkStickError>,
+}
+
+struct Shared {
+    control: Mutex<Control>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn control(&self) -> MutexGuard<'_, Control> {
+        self.control.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn wait<'a>(&self, control: MutexGuard<'a, Control>) -> MutexGuard<'a, Control> {
+        self.changed
+            .wait(control)
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Control)) {
+        change(&mut self.control());
+        self.changed.notify_all();
+    }
+}
+
+/// An animation that did not stop cleanly
+///
+/// Holds the device whenever it is still usable, so a failed end-state write
+/// does not close it.
+pub struct StopError<T: Transport = UsbTransport> {
+    error: BlinkStickError,
+    // Boxed to keep `Result`s carrying this error small
+    device: Option<Box<BlinkStick<T>>>,
+}
+
+impl<T: Transport> StopError<T> {
+    /// What went wrong
+    pub fn error(&self) -> &BlinkStickError {
+        &self.error
+    }
+
+    /// The device, unless the animation thread died with it
+    pub fn into_device(self) -> Option<BlinkStick<T>> {
+        self.device.map(|device| *device)
+    }
+
+    /// Split into the error and the device
+    pub fn into_parts(self) -> (BlinkStickError, Option<BlinkStick<T>>) {
+        (self.error, self.device.map(|device| *device))
+    }
+}
+
+impl<T: Transport> From<StopError<T>> for BlinkStickError {
+    fn from(err: StopError<T>) -> Self {
+        err.error
+    }
+}
+
+impl<T: Transport> fmt::Debug for StopError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StopError")
+            .field("error", &self.error)
+            .field("device", &self.device.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<T: Transport> fmt::Display for StopError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to stop the animation: {}", self.error)
+    }
+}
+
+impl<T: Transport> std::error::Error for StopError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Controls a running animation
+///
+/// Dropping the handle stops the animation like `stop` and closes the device.
+pub struct AnimationHandle<T: Transport + Send + 'static = UsbTransport> {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<Result<BlinkStick<T>, StopError<T>>>>,
+}
+
+impl<T: Transport + Send + 'static> AnimationHandle<T> {
+    /// Freeze the animation on its current frame
+    pub fn pause(&self) {
+        self.shared.update(|control| {
+            if control.state == RunState::Running {
+                control.state = RunState::Paused;
+            }
+        });
+    }
+
+    /// Continue a paused animation where it left off
+    pub fn resume(&self) {
+        self.shared.update(|control| {
+            if control.state == RunState::Paused {
+                control.state = RunState::Running;
+            }
+        });
+    }
+
+    /// Whether the animation is paused
+    pub fn is_paused(&self) -> bool {
+        self.shared.control().state == RunState::Paused
+    }
+
+    /// Switch to another effect, starting it from zero on the next frame
+    pub fn replace<E>(&self, effect: E)
+    where
+        E: Effect + Send + 'static,
+    {
+        self.shared.update(|control| {
+            control.replacement = Some(Box::new(effect));
+        });
+    }
+
+    /// The most recent failed frame transfer, if any
+    pub fn take_error(&self) -> Option<BlinkStickError> {
+        self.shared.control().error.take()
+    }
+
+    /// Stop the animation, apply the end state and return the device
+    ///
+    /// Fails if the end state could not be written; the error still carries
+    /// the device so it can be retried or reused.
+    pub fn stop(mut self) -> Result<BlinkStick<T>, StopError<T>> {
+        self.finish().unwrap_or(Err(StopError {
+            error: BlinkStickError::WorkerStopped,
+            device: None,
+        }))
+    }
+
+    fn finish(&mut self) -> Option<Result<BlinkStick<T>, StopError<T>>> {
+        self.shared
+            .update(|control| control.state = RunState::Stopping);
+
+        self.thread.take()?.join().ok()
+    }
+}
+
+impl<T: Transport + Send + 'static> Drop for AnimationHandle<T> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
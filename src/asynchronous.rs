@@ -0,0 +1,186 @@
+//! Async BlinkStick API for tokio applications.
+//!
+//! USB transfers are blocking, so every `AsyncBlinkStick` owns a dedicated
+//! worker thread holding the device. Async methods queue a job for the worker
+//! and await its reply without ever blocking the runtime. Animations sleep with
+//! `tokio::time` and stop as soon as their future is dropped.
+
+use crate::{
+    pulse_levels, BlinkStick, BlinkStickBuilder, BlinkStickError, BlinkStickVariant, Easing,
+    Result, RgbColor, Transport, UsbTransport,
+};
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::oneshot;
+
+type Job<T> = Box<dyn FnOnce(&BlinkStick<T>) + Send>;
+
+/// BlinkStick driven from async code
+///
+/// Clones share the same worker, so jobs from all clones run in order. The
+/// worker thread exits once the last clone is dropped.
+pub struct AsyncBlinkStick<T: Transport + Send + 'static = UsbTransport> {
+    jobs: mpsc::Sender<Job<T>>,
+    variant: BlinkStickVariant,
+}
+
+impl<T: Transport + Send + 'static> Clone for AsyncBlinkStick<T> {
+    fn clone(&self) -> Self {
+        Self {
+            jobs: self.jobs.clone(),
+            variant: self.variant,
+        }
+    }
+}
+
+impl AsyncBlinkStick<UsbTransport> {
+    /// Open the first BlinkStick
+    pub async fn find_first() -> Result<Self> {
+        Self::open(BlinkStick::builder()).await
+    }
+
+    /// Open the device described by `builder` on the worker thread
+    pub async fn open(builder: BlinkStickBuilder) -> Result<Self> {
+        Self::spawn(move || builder.open()).await
+    }
+}
+
+impl<T: Transport + Send + 'static> AsyncBlinkStick<T> {
+    /// Move an opened device onto a worker thread
+    pub fn new(device: BlinkStick<T>) -> Self {
+        let variant = device.variant();
+        let (jobs, queue) = mpsc::channel::<Job<T>>();
+
+        thread::spawn(move || serve(device, queue));
+
+        Self { jobs, variant }
+    }
+
+    /// Open a device with `connect` on a new worker thread
+    pub async fn spawn<F>(connect: F) -> Result<Self>
+    where
+        F: FnOnce() -> Result<BlinkStick<T>> + Send + 'static,
+    {
+        let (opened, result) = oneshot::channel();
+        let (jobs, queue) = mpsc::channel::<Job<T>>();
+
+        thread::spawn(move || {
+            let device = match connect() {
+                Ok(device) => device,
+                Err(err) => {
+                    let _ = opened.send(Err(err));
+                    return;
+                }
+            };
+
+            if opened.send(Ok(device.variant())).is_ok() {
+                serve(device, queue);
+            }
+        });
+
+        let variant = result.await.map_err(|_| BlinkStickError::WorkerStopped)??;
+
+        Ok(Self { jobs, variant })
+    }
+
+    /// The hardware variant of the device
+    pub fn variant(&self) -> BlinkStickVariant {
+        self.variant
+    }
+
+    /// Run a blocking operation on the worker thread and await its result
+    ///
+    /// Dropping the returned future does not cancel an operation that has
+    /// already started, but its result is discarded.
+    pub async fn run<R, F>(&self, op: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&BlinkStick<T>) -> Result<R> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+
+        self.jobs
+            .send(Box::new(move |device| {
+                let _ = reply.send(op(device));
+            }))
+            .map_err(|_| BlinkStickError::WorkerStopped)?;
+
+        result.await.map_err(|_| BlinkStickError::WorkerStopped)?
+    }
+
+    /// Set the color of the first LED
+    pub async fn set_color(&self, color: &RgbColor) -> Result<()> {
+        let color = color.clone();
+        self.run(move |device| device.set_color(&color)).await
+    }
+
+    /// Set the color of a single LED on a channel
+    pub async fn set_color_at(&self, channel: u8, index: u8, color: &RgbColor) -> Result<()> {
+        let color = color.clone();
+        self.run(move |device| device.set_color_at(channel, index, &color))
+            .await
+    }
+
+    /// Set colors for multiple LEDs at once
+    pub async fn set_colors(&self, channel: u8, leds: &[RgbColor]) -> Result<()> {
+        let leds = leds.to_vec();
+        self.run(move |device| device.set_colors(channel, &leds))
+            .await
+    }
+
+    /// Fill all LEDs on a channel with the same color
+    pub async fn set_all_colors(&self, channel: u8, led_count: u8, color: &RgbColor) -> Result<()> {
+        let color = color.clone();
+        self.run(move |device| device.set_all_colors(channel, led_count, &color))
+            .await
+    }
+
+    /// Get the current color of the first LED
+    pub async fn get_color(&self) -> Result<RgbColor> {
+        self.run(|device| device.get_color()).await
+    }
+
+    /// Attempt to read the number of LEDs available on a given channel
+    pub async fn get_led_count(&self, channel: u8) -> Result<u8> {
+        self.run(move |device| device.get_led_count(channel)).await
+    }
+
+    /// Get the device serial number
+    pub async fn get_serial(&self) -> Result<String> {
+        self.run(|device| device.get_serial()).await
+    }
+
+    /// Create a pulse effect without blocking the runtime
+    ///
+    /// Fades the first LED in and out in `steps` steps each way, leaving it
+    /// off. Dropping the future stops the animation where it is.
+    pub async fn pulse(&self, color: &RgbColor, duration_ms: u32, steps: u32) -> Result<()> {
+        self.pulse_with(color, duration_ms, steps, Easing::Linear)
+            .await
+    }
+
+    /// Create a pulse effect whose brightness follows `easing`
+    pub async fn pulse_with(
+        &self,
+        color: &RgbColor,
+        duration_ms: u32,
+        steps: u32,
+        easing: Easing,
+    ) -> Result<()> {
+        let (step_delay, levels) = pulse_levels(duration_ms, steps, easing)?;
+
+        for factor in levels {
+            self.set_color(&color.scaled(factor)).await?;
+            tokio::time::sleep(step_delay).await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Worker loop: run jobs until every handle is gone, then close the device
+fn serve<T: Transport>(device: BlinkStick<T>, queue: mpsc::Receiver<Job<T>>) {
+    for job in queue {
+        job(&device);
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-device transfer settings and a builder for opening BlinkSticks with them.
+
+use crate::{BlinkStick, DevicePath, DeviceSelector, Result, Transport};
+use std::time::Duration;
+
+/// Default timeout for a single control transfer
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Default pause before a failed transfer is retried
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// How control transfers are issued and retried
+///
+/// Only transient failures (see [`crate::BlinkStickError::is_transient`]) are
+/// retried; a disconnect or permission error is returned straight away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferOptions {
+    /// Timeout for a single control transfer
+    pub timeout: Duration,
+    /// How many times a transiently failing transfer is repeated
+    pub retries: u32,
+    /// Pause between two attempts of the same transfer
+    pub retry_delay: Duration,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+}
+
+/// Opens a BlinkStick with custom transfer settings
+///
+/// ```no_run
+/// # use blinkstick::BlinkStick;
+/// # use std::time::Duration;
+/// let blinkstick = BlinkStick::builder()
+///     .serial("BS012345-3.0")
+///     .timeout(Duration::from_millis(200))
+///     .retries(3)
+///     .open()?;
+/// # Ok::<(), blinkstick::BlinkStickError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BlinkStickBuilder {
+    selector: DeviceSelector,
+    options: TransferOptions,
+    turn_off_on_close: bool,
+}
+
+impl BlinkStickBuilder {
+    /// Start with the first device and default transfer settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose which device `open` picks
+    pub fn selector(mut self, selector: DeviceSelector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Pick the device with the given serial number
+    pub fn serial(self, serial: &str) -> Self {
+        self.selector(DeviceSelector::Serial(serial.to_string()))
+    }
+
+    /// Pick the device whose info block 1 holds the given name
+    pub fn name(self, name: &str) -> Self {
+        self.selector(DeviceSelector::Name(name.to_string()))
+    }
+
+    /// Pick the device plugged into the given bus and port chain
+    pub fn path(self, path: DevicePath) -> Self {
+        self.selector(DeviceSelector::Path(path))
+    }
+
+    /// Replace all transfer settings at once
+    pub fn options(mut self, options: TransferOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Timeout for a single control transfer
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// Number of retries for transiently failing transfers
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// Pause between two attempts of the same transfer
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.options.retry_delay = retry_delay;
+        self
+    }
+
+    /// Blank the LEDs written through the device when it is dropped
+    pub fn turn_off_on_close(mut self, turn_off: bool) -> Self {
+        self.turn_off_on_close = turn_off;
+        self
+    }
+
+    /// Open the selected device over libusb
+    pub fn open(&self) -> Result<BlinkStick> {
+        Ok(self.configure(BlinkStick::open_selected(&self.selector)?))
+    }
+
+    /// Drive a BlinkStick through an arbitrary transport with these settings
+    ///
+    /// The selector is ignored since the transport already is the device.
+    pub fn build<T: Transport>(&self, transport: T) -> BlinkStick<T> {
+        self.configure(BlinkStick::new(transport))
+    }
+
+    fn configure<T: Transport>(&self, blinkstick: BlinkStick<T>) -> BlinkStick<T> {
+        blinkstick
+            .with_options(self.options.clone())
+            .with_turn_off_on_close(self.turn_off_on_close)
+    }
+}
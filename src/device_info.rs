@@ -0,0 +1,91 @@
+//! Description of an enumerated BlinkStick.
+
+use crate::selector::{DeviceCandidate, DevicePath};
+use crate::{transport, BlinkStick, BlinkStickError, BlinkStickVariant, Operation, Result};
+use rusb::{Context, Device};
+use serde::Serialize;
+
+/// Everything enumeration can tell about a connected BlinkStick
+///
+/// The strings are only available when the device could be opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceInfo {
+    pub serial: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// bcdDevice release number, e.g. `2.0.1`
+    pub firmware_version: String,
+    pub variant: BlinkStickVariant,
+    pub bus: u8,
+    pub address: u8,
+    pub path: DevicePath,
+    /// Whether the device could be opened with the current permissions
+    pub openable: bool,
+}
+
+impl DeviceInfo {
+    /// Describe a libusb device
+    pub fn from_device(device: &Device<Context>) -> Result<Self> {
+        let descriptor = device
+            .device_descriptor()
+            .map_err(BlinkStickError::usb(Operation::ReadDescriptor, None))?;
+        let version = descriptor.device_version();
+
+        let mut info = Self {
+            serial: None,
+            manufacturer: None,
+            product: None,
+            firmware_version: version.to_string(),
+            variant: BlinkStickVariant::Unknown,
+            bus: device.bus_number(),
+            address: device.address(),
+            path: DevicePath::new(
+                device.bus_number(),
+                device.port_numbers().unwrap_or_default(),
+            ),
+            openable: false,
+        };
+
+        if let Ok(handle) = device.open() {
+            info.openable = true;
+            info.serial = handle.read_serial_number_string_ascii(&descriptor).ok();
+            info.manufacturer = handle.read_manufacturer_string_ascii(&descriptor).ok();
+            info.product = handle.read_product_string_ascii(&descriptor).ok();
+        }
+
+        if let Some(serial) = &info.serial {
+            info.variant = BlinkStickVariant::detect(serial, transport::version_to_bcd(version));
+        }
+
+        Ok(info)
+    }
+
+    /// Open the device described by this entry
+    pub fn open(&self) -> Result<BlinkStick> {
+        let device = BlinkStick::find_devices()?
+            .into_iter()
+            .find(|d| d.bus_number() == self.bus && d.address() == self.address)
+            .ok_or_else(|| {
+                BlinkStickError::NoMatchingDevice(crate::DeviceSelector::Path(self.path.clone()))
+            })?;
+
+        BlinkStick::open(device)
+    }
+}
+
+impl DeviceCandidate for DeviceInfo {
+    fn serial(&self) -> Option<String> {
+        self.serial.clone()
+    }
+
+    /// Opens the device to read info block 1
+    ///
+    /// `BlinkStick::open_selected` avoids this and reads each name once.
+    fn name(&self) -> Option<String> {
+        self.open().ok()?.name().ok()
+    }
+
+    fn path(&self) -> Option<DevicePath> {
+        Some(self.path.clone())
+    }
+}
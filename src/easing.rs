@@ -0,0 +1,189 @@
+//! Easing curves that shape how transitions progress over time.
+
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Maps linear progress from 0.0 to 1.0 onto a curve
+///
+/// The polynomial, sine and exponential curves ease in and out, so they start
+/// and end slowly. Bounce and elastic overshoot or rebound at the end.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Squared ease in and out
+    Quadratic,
+    /// Cubed ease in and out
+    Cubic,
+    /// Half a cosine wave
+    Sine,
+    /// Very slow start and end with a steep middle
+    Exponential,
+    /// Bounces a few times before settling at the end
+    Bounce,
+    /// Overshoots and springs back before settling at the end
+    Elastic,
+    /// CSS-style cubic Bézier through (0, 0), (x1, y1), (x2, y2) and (1, 1)
+    ///
+    /// The x coordinates are clamped to 0.0..=1.0 so the curve stays a
+    /// function of time.
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// Eased progress for linear progress `t`, which is clamped to 0.0..=1.0
+    ///
+    /// Every curve maps 0.0 to 0.0 and 1.0 to 1.0. Values in between may leave
+    /// that range for elastic and Bézier curves.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match *self {
+            Self::Linear => t,
+            Self::Quadratic => in_out(t, |t| t * t),
+            Self::Cubic => in_out(t, |t| t * t * t),
+            Self::Sine => (1.0 - (PI * t).cos()) / 2.0,
+            Self::Exponential => match t {
+                0.0 | 1.0 => t,
+                _ => in_out(t, |t| 2f32.powf(10.0 * t - 10.0)),
+            },
+            Self::Bounce => bounce(t),
+            Self::Elastic => match t {
+                0.0 | 1.0 => t,
+                _ => 2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0,
+            },
+            Self::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t)
+            }
+        }
+    }
+}
+
+/// Mirror an ease-in curve into an ease-in-out curve
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+fn bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    // One coordinate of the curve at parameter s, with both ends fixed
+    let curve = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * a + 3.0 * inv * s * s * b + s * s * s
+    };
+    let slope = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * a + 6.0 * inv * s * (b - a) + 3.0 * s * s * (1.0 - b)
+    };
+
+    // Newton's method converges quickly on most curves; bisection catches
+    // the flat spots where it does not
+    let mut s = x;
+    for _ in 0..8 {
+        let error = curve(x1, x2, s) - x;
+        let d = slope(x1, x2, s);
+
+        if error.abs() < 1e-6 {
+            return curve(y1, y2, s);
+        }
+        if d.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / d).clamp(0.0, 1.0);
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    s = x;
+    for _ in 0..32 {
+        if curve(x1, x2, s) < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+
+    curve(y1, y2, s)
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => f.write_str("linear"),
+            Self::Quadratic => f.write_str("quadratic"),
+            Self::Cubic => f.write_str("cubic"),
+            Self::Sine => f.write_str("sine"),
+            Self::Exponential => f.write_str("exponential"),
+            Self::Bounce => f.write_str("bounce"),
+            Self::Elastic => f.write_str("elastic"),
+            Self::CubicBezier(x1, y1, x2, y2) => {
+                write!(f, "cubic-bezier({},{},{},{})", x1, y1, x2, y2)
+            }
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+
+        if let Some(points) = name
+            .strip_prefix("cubic-bezier(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let points = points
+                .split(',')
+                .map(|point| point.trim().parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Invalid cubic-bezier point in {}: {}", s, e))?;
+
+            return match points[..] {
+                [x1, y1, x2, y2] => Ok(Self::CubicBezier(x1, y1, x2, y2)),
+                _ => Err(format!(
+                    "Invalid easing: {} (cubic-bezier takes 4 numbers)",
+                    s
+                )),
+            };
+        }
+
+        match name.as_str() {
+            "linear" => Ok(Self::Linear),
+            "quadratic" | "quad" => Ok(Self::Quadratic),
+            "cubic" => Ok(Self::Cubic),
+            "sine" => Ok(Self::Sine),
+            "exponential" | "expo" => Ok(Self::Exponential),
+            "bounce" => Ok(Self::Bounce),
+            "elastic" => Ok(Self::Elastic),
+            _ => Err(format!(
+                "Invalid easing: {} (expected linear, quadratic, cubic, sine, exponential, \
+                 bounce, elastic or cubic-bezier(x1,y1,x2,y2))",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Frame-based effects and the engine that plays them.
+//!
+//! An [`Effect`] renders a whole channel for a point in time. The [`Engine`]
+//! owns the timing: it renders frames at a fixed rate and sends each one with
+//! `BlinkStick::set_colors`. Closures taking the elapsed time and the frame are
+//! effects too, so one-off patterns need no type of their own.
+
+use crate::effects::{Blink, BlinkOptions, BlinkTarget, Morph};
+use crate::{BlinkStick, Easing, Result, RgbColor, Transport};
+use std::ops::Range;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default frame rate of the engine
+pub const DEFAULT_FPS: u32 = 50;
+
+/// A pattern rendered frame by frame
+pub trait Effect {
+    /// Render the frame for `elapsed` time since the effect started
+    ///
+    /// `leds` holds the previous frame, so effects may update it in place.
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]);
+
+    /// Total running time, or `None` if the effect loops forever
+    fn duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl<F> Effect for F
+where
+    F: FnMut(Duration, &mut [RgbColor]),
+{
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        self(elapsed, leds)
+    }
+}
+
+/// Plays effects on one channel at a target frame rate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Engine {
+    pub(crate) channel: u8,
+    pub(crate) led_count: usize,
+    pub(crate) frame_interval: Duration,
+}
+
+impl Engine {
+    /// Play on channel 0 with `led_count` LEDs at the default frame rate
+    pub fn new(led_count: u8) -> Self {
+        Self {
+            channel: 0,
+            led_count: led_count as usize,
+            frame_interval: Duration::from_secs(1) / DEFAULT_FPS,
+        }
+    }
+
+    /// Play on channel 0 with the LED count of the device model
+    pub fn for_device<T: Transport>(device: &BlinkStick<T>) -> Self {
+        Self::new(device.variant().default_led_count())
+    }
+
+    /// Channel frames are sent to
+    pub fn channel(mut self, channel: u8) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Number of LEDs in each frame
+    pub fn led_count(mut self, led_count: u8) -> Self {
+        self.led_count = led_count as usize;
+        self
+    }
+
+    /// Target frames per second
+    pub fn fps(self, fps: u32) -> Self {
+        self.frame_interval(Duration::from_secs(1) / fps.max(1))
+    }
+
+    /// Time between two frames
+    pub fn frame_interval(mut self, frame_interval: Duration) -> Self {
+        self.frame_interval = frame_interval;
+        self
+    }
+
+    /// An all-off frame of the configured size
+    pub(crate) fn blank_frame(&self) -> Vec<RgbColor> {
+        vec![RgbColor::default(); self.led_count]
+    }
+
+    /// Play an effect until it ends
+    ///
+    /// The last frame is rendered at exactly the effect's duration. Effects
+    /// without a duration play until a transfer fails.
+    pub fn play<T, E>(&self, device: &BlinkStick<T>, effect: &mut E) -> Result<()>
+    where
+        T: Transport,
+        E: Effect + ?Sized,
+    {
+        self.play_until(device, effect, effect.duration())
+    }
+
+    /// Play an effect for at most `duration`
+    pub fn play_for<T, E>(
+        &self,
+        device: &BlinkStick<T>,
+        effect: &mut E,
+        duration: Duration,
+    ) -> Result<()>
+    where
+        T: Transport,
+        E: Effect + ?Sized,
+    {
+        let end = effect.duration().map_or(duration, |d| d.min(duration));
+        self.play_until(device, effect, Some(end))
+    }
+
+    fn play_until<T, E>(
+        &self,
+        device: &BlinkStick<T>,
+        effect: &mut E,
+        end: Option<Duration>,
+    ) -> Result<()>
+    where
+        T: Transport,
+        E: Effect + ?Sized,
+    {
+        let mut frame = self.blank_frame();
+        let start = Instant::now();
+        let mut next_frame = start;
+        let mut elapsed = Duration::ZERO;
+
+        loop {
+            let finished = end.is_some_and(|end| elapsed >= end);
+            let at = end.map_or(elapsed, |end| elapsed.min(end));
+
+            effect.render(at, &mut frame);
+            device.set_colors(self.channel, &frame)?;
+
+            if finished {
+                return Ok(());
+            }
+
+            // Frames that are already late are skipped rather than queued up
+            next_frame += self.frame_interval;
+            let now = Instant::now();
+
+            if next_frame > now {
+                let wait = next_frame - now;
+                thread::sleep(end.map_or(wait, |end| wait.min(end.saturating_sub(at))));
+            } else {
+                next_frame = now;
+            }
+
+            elapsed = start.elapsed();
+        }
+    }
+}
+
+impl<T: Transport> BlinkStick<T> {
+    /// Play an effect on channel 0 across the model's LEDs until it ends
+    pub fn play<E: Effect + ?Sized>(&self, effect: &mut E) -> Result<()> {
+        Engine::for_device(self).play(self, effect)
+    }
+
+    /// Smoothly fade channel 0 from what it shows now to `to`
+    ///
+    /// The starting colors come from the frame last written through this
+    /// handle. LEDs it does not cover start from the colors read back from
+    /// the device.
+    pub fn morph(&self, to: &RgbColor, duration: Duration) -> Result<()> {
+        self.morph_with(to, duration, Easing::Linear)
+    }
+
+    /// Like `morph`, with the blend following `easing`
+    pub fn morph_with(&self, to: &RgbColor, duration: Duration, easing: Easing) -> Result<()> {
+        let written = self.last_frame(0).map_or(0, |frame| frame.len());
+        let led_count = written.max(self.variant().default_led_count() as usize);
+        let from = self.current_frame(0, led_count)?;
+
+        Engine::for_device(self).led_count(led_count as u8).play(
+            self,
+            &mut Morph::new(from, to.clone(), duration).easing(easing),
+        )
+    }
+
+    /// Blink the first LED `repeats` times and leave it off
+    pub fn blink(&self, color: &RgbColor, repeats: u32, on: Duration, off: Duration) -> Result<()> {
+        let mut blink = Blink::new(color.clone(), on, off).times(repeats);
+        self.blink_with(&mut blink, &BlinkOptions::default())
+    }
+
+    /// Play a blink on some LEDs of a channel
+    ///
+    /// Frames cover the whole strip. The LEDs outside the target are rewritten
+    /// with the colors they show: the ones last written through this handle,
+    /// or else the ones read back from the device.
+    /// Transitions are only as precise as the engine's frame rate.
+    pub fn blink_with(&self, blink: &mut Blink, options: &BlinkOptions) -> Result<()> {
+        let strip = self.get_led_count(options.channel)?;
+        let leds = match &options.target {
+            BlinkTarget::Led(index) => *index..index.saturating_add(1),
+            BlinkTarget::Range(range) => range.clone(),
+            BlinkTarget::Channel => 0..strip,
+        };
+
+        if leds.is_empty() {
+            return Ok(());
+        }
+
+        // Shorter frames would zero the LEDs past them
+        let written = self
+            .last_frame(options.channel)
+            .map_or(0, |frame| frame.len());
+        let led_count = written.max(leds.end.max(strip) as usize);
+        let before = self.current_frame(options.channel, led_count)?;
+
+        let mut region = Region {
+            effect: blink,
+            leds: leds.start as usize..leds.end as usize,
+            background: &before,
+        };
+
+        Engine::new(led_count as u8)
+            .channel(options.channel)
+            .play(self, &mut region)?;
+
+        if options.restore {
+            self.set_colors(options.channel, &before)?;
+        }
+
+        Ok(())
+    }
+
+    /// The colors a channel shows as far as this handle knows
+    ///
+    /// LEDs past the last written frame are read back from the device. Models
+    /// without multi-LED reports only have the LED `get_color` reads.
+    fn current_frame(&self, channel: u8, led_count: usize) -> Result<Vec<RgbColor>> {
+        let mut frame = self.last_frame(channel).unwrap_or_default();
+
+        if frame.len() < led_count {
+            if self.variant().supports_multi_led() {
+                let shown = self.get_colors(channel, led_count)?;
+                frame.extend_from_slice(&shown[frame.len()..]);
+            } else {
+                frame.resize(led_count, self.get_color()?);
+            }
+        }
+
+        Ok(frame)
+    }
+}
+
+/// Renders an effect onto part of a frame and keeps the rest fixed
+struct Region<'a, E: ?Sized> {
+    effect: &'a mut E,
+    leds: Range<usize>,
+    background: &'a [RgbColor],
+}
+
+impl<E: Effect + ?Sized> Effect for Region<'_, E> {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        leds.clone_from_slice(self.background);
+        self.effect.render(elapsed, &mut leds[self.leds.clone()]);
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.effect.duration()
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{Effect, RgbColor};
+use std::ops::Range;
+use std::time::Duration;
+
+/// Switches every LED between a color and off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blink {
+    color: RgbColor,
+    on: Duration,
+    off: Duration,
+    count: Option<u32>,
+}
+
+impl Blink {
+    /// A single blink: `on` lit, then `off` dark
+    pub fn new(color: RgbColor, on: Duration, off: Duration) -> Self {
+        Self {
+            color,
+            on,
+            off,
+            count: Some(1),
+        }
+    }
+
+    /// A single blink lasting `period`, lit for the `duty` fraction of it
+    ///
+    /// `duty` is clamped to 0.0..=1.0.
+    pub fn duty_cycle(color: RgbColor, period: Duration, duty: f32) -> Self {
+        // Float rounding can land a few nanoseconds past the period
+        let on_nanos = (period.as_nanos() as f64 * duty.clamp(0.0, 1.0) as f64).round() as u128;
+        let on = Duration::from_nanos(on_nanos.min(period.as_nanos()) as u64);
+
+        Self::new(color, on, period.saturating_sub(on))
+    }
+
+    /// Blink `count` times
+    pub fn times(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Blink until stopped
+    pub fn forever(mut self) -> Self {
+        self.count = None;
+        self
+    }
+
+    fn period(&self) -> Duration {
+        self.on + self.off
+    }
+
+    fn is_lit(&self, elapsed: Duration) -> bool {
+        if self.period().is_zero() || self.duration().is_some_and(|end| elapsed >= end) {
+            return false;
+        }
+
+        elapsed.as_nanos() % self.period().as_nanos() < self.on.as_nanos()
+    }
+}
+
+impl Effect for Blink {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        if self.is_lit(elapsed) {
+            leds.fill(self.color.clone());
+        } else {
+            leds.fill(RgbColor::default());
+        }
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.count.map(|count| self.period() * count)
+    }
+}
+
+/// Which LEDs of a channel `BlinkStick::blink_with` blinks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlinkTarget {
+    /// A single LED
+    Led(u8),
+    /// The LEDs whose index lies in the range
+    Range(Range<u8>),
+    /// Every LED the channel reports
+    Channel,
+}
+
+impl Default for BlinkTarget {
+    fn default() -> Self {
+        BlinkTarget::Led(0)
+    }
+}
+
+/// Where `BlinkStick::blink_with` blinks and what is shown afterwards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlinkOptions {
+    /// Channel the blinking LEDs are on
+    pub channel: u8,
+    /// LEDs that blink
+    pub target: BlinkTarget,
+    /// Show the previous colors once done instead of leaving the LEDs off
+    pub restore: bool,
+}
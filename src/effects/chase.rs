@@ -0,0 +1,110 @@
+use super::{Direction, Motion};
+use crate::{Effect, RgbColor};
+use std::time::Duration;
+
+/// Lit pixels moving along the strip one LED per step, with a fading tail
+///
+/// The same effect covers a single-dot chase, a theater marquee lighting every
+/// Nth LED and a Larson scanner sweeping back and forth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chase {
+    color: RgbColor,
+    step: Duration,
+    spacing: Option<usize>,
+    tail: usize,
+    motion: Motion,
+    direction: Direction,
+}
+
+impl Chase {
+    /// A single dot running to the end of the strip and starting over
+    pub fn new(color: RgbColor, step: Duration) -> Self {
+        Self {
+            color,
+            step,
+            spacing: None,
+            tail: 0,
+            motion: Motion::Wrap,
+            direction: Direction::Forward,
+        }
+    }
+
+    /// Every `spacing`th LED lit, the pattern shifting one LED per step
+    pub fn marquee(color: RgbColor, spacing: usize, step: Duration) -> Self {
+        Self {
+            spacing: Some(spacing.max(1)),
+            ..Self::new(color, step)
+        }
+    }
+
+    /// A dot sweeping back and forth with a fading tail, like a Larson scanner
+    pub fn scanner(color: RgbColor, step: Duration) -> Self {
+        Self::new(color, step).tail(3).motion(Motion::Bounce)
+    }
+
+    /// Number of dimmer LEDs trailing each lit pixel
+    pub fn tail(mut self, tail: usize) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    /// What happens when a pixel reaches the end of the strip
+    pub fn motion(mut self, motion: Motion) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    /// Which end of the strip pixels start moving from
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Index of the head within `span` LEDs after `step` steps
+    fn position(&self, step: u64, span: usize) -> usize {
+        if span <= 1 {
+            return 0;
+        }
+
+        let span = span as u64;
+        let position = match self.motion {
+            Motion::Wrap => step % span,
+            Motion::Bounce => {
+                let round_trip = 2 * (span - 1);
+                let step = step % round_trip;
+                step.min(round_trip - step)
+            }
+        };
+
+        match self.direction {
+            Direction::Forward => position as usize,
+            Direction::Reverse => (span - 1 - position) as usize,
+        }
+    }
+}
+
+impl Effect for Chase {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        let steps = if self.step.is_zero() {
+            0
+        } else {
+            (elapsed.as_nanos() / self.step.as_nanos()) as u64
+        };
+        let span = self.spacing.unwrap_or(leds.len());
+        let mut levels = vec![0.0f32; leds.len()];
+
+        // Walk back through the previous head positions to draw the tail
+        for age in 0..=self.tail.min(steps as usize) {
+            let level = 1.0 - age as f32 / (self.tail + 1) as f32;
+            let head = self.position(steps - age as u64, span);
+
+            for index in (head..leds.len()).step_by(span.max(1)) {
+                levels[index] = levels[index].max(level);
+            }
+        }
+
+        for (led, level) in leds.iter_mut().zip(levels) {
+            *led = self.color.scaled(level);
+        }
+    }
+}
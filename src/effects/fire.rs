@@ -0,0 +1,120 @@
+use super::seeded::Simulation;
+use super::Direction;
+use crate::{Effect, RgbColor};
+use rand::Rng;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(16);
+
+/// Flickering flames rising from one end of the strip
+///
+/// Each LED holds a heat value. Every tick all cells cool down a little, heat
+/// drifts away from the base and new sparks ignite near it.
+#[derive(Debug, Clone)]
+pub struct Fire {
+    cooling: u8,
+    sparking: u8,
+    direction: Direction,
+    heat: Vec<u8>,
+    simulation: Simulation,
+}
+
+impl Default for Fire {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fire {
+    /// A medium fire burning from the first LED, with a random seed
+    pub fn new() -> Self {
+        Self {
+            cooling: 55,
+            sparking: 120,
+            direction: Direction::Forward,
+            heat: Vec::new(),
+            simulation: Simulation::new(TICK),
+        }
+    }
+
+    /// How fast the flames cool down; higher values give shorter flames
+    pub fn cooling(mut self, cooling: u8) -> Self {
+        self.cooling = cooling;
+        self
+    }
+
+    /// Chance out of 255 that a new spark ignites each tick
+    pub fn sparking(mut self, sparking: u8) -> Self {
+        self.sparking = sparking;
+        self
+    }
+
+    /// Burn from the first LED (forward) or from the last
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Make the flames reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.simulation.reseed(seed);
+        self
+    }
+
+    fn step(&mut self) {
+        let rng = &mut self.simulation.rng;
+        let led_count = self.heat.len();
+        let max_cooling = (self.cooling as usize * 10 / led_count + 2).min(255) as u8;
+
+        for cell in self.heat.iter_mut() {
+            *cell = cell.saturating_sub(rng.gen_range(0..=max_cooling));
+        }
+
+        for index in (2..led_count).rev() {
+            let below = self.heat[index - 1] as u16 + 2 * self.heat[index - 2] as u16;
+            self.heat[index] = (below / 3) as u8;
+        }
+
+        if rng.gen::<u8>() < self.sparking {
+            let index = rng.gen_range(0..led_count.min(7));
+            self.heat[index] = self.heat[index].saturating_add(rng.gen_range(160..=255));
+        }
+    }
+}
+
+/// Black through red and yellow to white
+fn heat_color(heat: u8) -> RgbColor {
+    let scaled = (heat as u16 * 191 / 255) as u8;
+    let ramp = (scaled & 0x3F) << 2;
+
+    match scaled {
+        0x80.. => RgbColor::new(255, 255, ramp),
+        0x40.. => RgbColor::new(255, ramp, 0),
+        _ => RgbColor::new(ramp, 0, 0),
+    }
+}
+
+impl Effect for Fire {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        if leds.is_empty() {
+            return;
+        }
+
+        let (restart, steps) = self.simulation.advance(elapsed, leds.len());
+        if restart {
+            self.heat = vec![0; leds.len()];
+        }
+
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (index, heat) in self.heat.iter().enumerate() {
+            let index = match self.direction {
+                Direction::Forward => index,
+                Direction::Reverse => leds.len() - 1 - index,
+            };
+            leds[index] = heat_color(*heat);
+        }
+    }
+}
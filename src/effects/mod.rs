@@ -0,0 +1,43 @@
+//! Built-in effects.
+//!
+//! Every effect implements [`Effect`](crate::Effect) and can be played with an
+//! [`Engine`](crate::Engine) or handed to an [`Animator`](crate::Animator).
+
+mod blink;
+mod chase;
+mod fire;
+mod morph;
+mod pulse;
+mod rainbow;
+mod seeded;
+mod sparkle;
+mod twinkle;
+
+pub use blink::{Blink, BlinkOptions, BlinkTarget};
+pub use chase::Chase;
+pub use fire::Fire;
+pub use morph::Morph;
+pub use pulse::Pulse;
+pub use rainbow::Rainbow;
+pub use sparkle::Sparkle;
+pub use twinkle::Twinkle;
+
+/// Which way a pattern travels along the strip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// From the first LED towards the last
+    #[default]
+    Forward,
+    /// From the last LED towards the first
+    Reverse,
+}
+
+/// What a moving pattern does at the end of the strip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Motion {
+    /// Start over at the other end
+    #[default]
+    Wrap,
+    /// Turn around and run back
+    Bounce,
+}
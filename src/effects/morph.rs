@@ -0,0 +1,56 @@
+use crate::{Easing, Effect, RgbColor};
+use std::time::Duration;
+
+/// Blends every LED from its own starting color to one target color
+#[derive(Debug, Clone, PartialEq)]
+pub struct Morph {
+    from: Vec<RgbColor>,
+    to: RgbColor,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Morph {
+    /// Morph from `from` to `to` over `duration`
+    ///
+    /// LEDs past the end of `from` start at its last color.
+    pub fn new(from: Vec<RgbColor>, to: RgbColor, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Curve the blend follows from start to end
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    fn progress(&self, elapsed: Duration) -> f32 {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f64() / self.duration.as_secs_f64()) as f32
+        };
+
+        self.easing.apply(t)
+    }
+}
+
+impl Effect for Morph {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        let t = self.progress(elapsed);
+
+        for (index, led) in leds.iter_mut().enumerate() {
+            let start = self.from.get(index).or(self.from.last()).cloned();
+            *led = start.unwrap_or_default().lerp(&self.to, t);
+        }
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
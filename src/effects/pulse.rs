@@ -0,0 +1,62 @@
+use crate::{Easing, Effect, RgbColor};
+use std::time::Duration;
+
+/// Fades every LED from off to a color and back
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pulse {
+    color: RgbColor,
+    period: Duration,
+    count: Option<u32>,
+    easing: Easing,
+}
+
+impl Pulse {
+    /// A single pulse lasting `period`, half fading in and half fading out
+    pub fn new(color: RgbColor, period: Duration) -> Self {
+        Self {
+            color,
+            period,
+            count: Some(1),
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Pulse `count` times
+    pub fn times(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Pulse until stopped
+    pub fn forever(mut self) -> Self {
+        self.count = None;
+        self
+    }
+
+    /// Curve the brightness follows while fading in and out
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Brightness between 0.0 and 1.0 at `elapsed`
+    fn level(&self, elapsed: Duration) -> f32 {
+        if self.period.is_zero() || self.duration().is_some_and(|end| elapsed >= end) {
+            return 0.0;
+        }
+
+        let phase = (elapsed.as_secs_f64() / self.period.as_secs_f64()).fract();
+
+        self.easing.apply((1.0 - (2.0 * phase - 1.0).abs()) as f32)
+    }
+}
+
+impl Effect for Pulse {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        leds.fill(self.color.scaled(self.level(elapsed)));
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.count.map(|count| self.period * count)
+    }
+}
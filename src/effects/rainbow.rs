@@ -0,0 +1,87 @@
+use super::Direction;
+use crate::{Effect, RgbColor};
+use std::time::Duration;
+
+/// Cycles through the color wheel, either on the whole strip or spread along it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rainbow {
+    period: Duration,
+    moving: bool,
+    saturation: f32,
+    brightness: f32,
+    direction: Direction,
+}
+
+impl Rainbow {
+    /// Every LED shows the same hue, going around the wheel once per `period`
+    pub fn cycle(period: Duration) -> Self {
+        Self {
+            period,
+            moving: false,
+            saturation: 1.0,
+            brightness: 1.0,
+            direction: Direction::Forward,
+        }
+    }
+
+    /// The whole wheel is spread over the strip and travels one strip length
+    /// per `period`
+    pub fn moving(period: Duration) -> Self {
+        Self {
+            moving: true,
+            ..Self::cycle(period)
+        }
+    }
+
+    /// Color saturation between 0.0 (white) and 1.0 (full color)
+    pub fn saturation(mut self, saturation: f32) -> Self {
+        self.saturation = saturation.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Brightness between 0.0 and 1.0
+    pub fn brightness(mut self, brightness: f32) -> Self {
+        self.brightness = brightness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Which way the colors travel
+    ///
+    /// Forward runs the hues from red towards green and moves the pattern
+    /// towards the last LED.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// How far around the wheel the cycle is at `elapsed`, from 0.0 to 1.0
+    fn phase(&self, elapsed: Duration) -> f32 {
+        if self.period.is_zero() {
+            return 0.0;
+        }
+
+        (elapsed.as_secs_f64() / self.period.as_secs_f64()).fract() as f32
+    }
+}
+
+impl Effect for Rainbow {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        let phase = self.phase(elapsed);
+        let led_count = leds.len() as f32;
+        let sign = match self.direction {
+            Direction::Forward => 1.0,
+            Direction::Reverse => -1.0,
+        };
+
+        for (index, led) in leds.iter_mut().enumerate() {
+            let offset = if self.moving {
+                index as f32 / led_count
+            } else {
+                0.0
+            };
+            let hue = (sign * phase - offset) * 360.0;
+
+            *led = RgbColor::from_hsv(hue, self.saturation, self.brightness);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Duration;
+
+/// Fixed-step random simulation shared by the procedural effects
+///
+/// The simulation advances in whole ticks, so the frame for a given elapsed
+/// time depends only on the seed and not on the frame rate. Rendering an
+/// earlier time than before replays the simulation from the start.
+#[derive(Debug, Clone)]
+pub(super) struct Simulation {
+    seed: u64,
+    tick: Duration,
+    steps: u64,
+    led_count: usize,
+    pub(super) rng: StdRng,
+}
+
+impl Simulation {
+    /// A simulation advancing once per `tick`, with a random seed
+    pub(super) fn new(tick: Duration) -> Self {
+        let seed = rand::random();
+
+        Self {
+            seed,
+            tick,
+            steps: 0,
+            led_count: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Replay from the start with a fixed seed
+    pub(super) fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.restart(self.led_count);
+    }
+
+    /// Steps needed to catch up with `elapsed` on a strip of `led_count` LEDs
+    ///
+    /// Returns `true` first if the effect must reset its state because the
+    /// simulation is starting from the beginning.
+    pub(super) fn advance(&mut self, elapsed: Duration, led_count: usize) -> (bool, u64) {
+        let target = if self.tick.is_zero() {
+            0
+        } else {
+            (elapsed.as_nanos() / self.tick.as_nanos()) as u64
+        };
+
+        let restart = self.steps == 0 || target < self.steps || led_count != self.led_count;
+        if restart {
+            self.restart(led_count);
+        }
+
+        // The first step runs at zero so the opening frame is not blank
+        let steps = target + 1 - self.steps;
+        self.steps = target + 1;
+
+        (restart, steps)
+    }
+
+    fn restart(&mut self, led_count: usize) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.steps = 0;
+        self.led_count = led_count;
+    }
+}
@@ -0,0 +1,86 @@
+use super::seeded::Simulation;
+use crate::{Effect, RgbColor};
+use rand::Rng;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(20);
+
+/// Short flashes at random LEDs over a steady base color
+#[derive(Debug, Clone)]
+pub struct Sparkle {
+    base: RgbColor,
+    flash: RgbColor,
+    density: f32,
+    fade_ticks: u32,
+    // How bright the flash at each LED still is, from 0.0 to 1.0
+    flashes: Vec<f32>,
+    simulation: Simulation,
+}
+
+impl Sparkle {
+    /// White flashes over `base`, with a random seed
+    pub fn new(base: RgbColor) -> Self {
+        Self {
+            base,
+            flash: RgbColor::new(255, 255, 255),
+            density: 0.02,
+            fade_ticks: 5,
+            flashes: Vec::new(),
+            simulation: Simulation::new(TICK),
+        }
+    }
+
+    /// Color of the flashes
+    pub fn flash(mut self, flash: RgbColor) -> Self {
+        self.flash = flash;
+        self
+    }
+
+    /// Chance per tick that an LED flashes, between 0.0 and 1.0
+    pub fn density(mut self, density: f32) -> Self {
+        self.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How long a flash takes to fade back to the base color
+    pub fn fade_time(mut self, fade_time: Duration) -> Self {
+        self.fade_ticks = (fade_time.as_nanos() / TICK.as_nanos()).max(1) as u32;
+        self
+    }
+
+    /// Make the flashes reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.simulation.reseed(seed);
+        self
+    }
+
+    fn step(&mut self) {
+        let rng = &mut self.simulation.rng;
+        let fade = 1.0 / self.fade_ticks as f32;
+
+        for flash in self.flashes.iter_mut() {
+            *flash = if rng.gen::<f32>() < self.density {
+                1.0
+            } else {
+                (*flash - fade).max(0.0)
+            };
+        }
+    }
+}
+
+impl Effect for Sparkle {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        let (restart, steps) = self.simulation.advance(elapsed, leds.len());
+        if restart {
+            self.flashes = vec![0.0; leds.len()];
+        }
+
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (led, flash) in leds.iter_mut().zip(&self.flashes) {
+            *led = self.base.lerp(&self.flash, *flash);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::seeded::Simulation;
+use crate::{Effect, RgbColor};
+use rand::Rng;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_millis(20);
+
+/// Stars lighting up at random LEDs, each fading in and back out
+#[derive(Debug, Clone)]
+pub struct Twinkle {
+    color: Option<RgbColor>,
+    density: f32,
+    lifetime: u32,
+    // Color and age in ticks of the star at each LED
+    stars: Vec<Option<(RgbColor, u32)>>,
+    simulation: Simulation,
+}
+
+impl Twinkle {
+    /// Stars of one color, with a random seed
+    pub fn new(color: RgbColor) -> Self {
+        Self {
+            color: Some(color),
+            ..Self::colorful()
+        }
+    }
+
+    /// Stars of random hues, with a random seed
+    pub fn colorful() -> Self {
+        Self {
+            color: None,
+            density: 0.02,
+            lifetime: 50,
+            stars: Vec::new(),
+            simulation: Simulation::new(TICK),
+        }
+    }
+
+    /// Chance per tick that a dark LED lights up, between 0.0 and 1.0
+    pub fn density(mut self, density: f32) -> Self {
+        self.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How long one star takes to fade in and out
+    pub fn twinkle_time(mut self, twinkle_time: Duration) -> Self {
+        self.lifetime = (twinkle_time.as_nanos() / TICK.as_nanos()).max(2) as u32;
+        self
+    }
+
+    /// Make the stars reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.simulation.reseed(seed);
+        self
+    }
+
+    fn step(&mut self) {
+        let rng = &mut self.simulation.rng;
+
+        for star in self.stars.iter_mut() {
+            match star {
+                Some((_, age)) if *age + 1 < self.lifetime => *age += 1,
+                Some(_) => *star = None,
+                None if rng.gen::<f32>() < self.density => {
+                    let color = match &self.color {
+                        Some(color) => color.clone(),
+                        None => RgbColor::from_hsv(rng.gen_range(0.0..360.0), 1.0, 1.0),
+                    };
+                    *star = Some((color, 0));
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Brightness of a star of `age` ticks, rising and falling linearly
+    fn level(&self, age: u32) -> f32 {
+        let phase = age as f32 / self.lifetime as f32;
+
+        1.0 - (2.0 * phase - 1.0).abs()
+    }
+}
+
+impl Effect for Twinkle {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        let (restart, steps) = self.simulation.advance(elapsed, leds.len());
+        if restart {
+            self.stars = vec![None; leds.len()];
+        }
+
+        for _ in 0..steps {
+            self.step();
+        }
+
+        for (led, star) in leds.iter_mut().zip(&self.stars) {
+            *led = match star {
+                Some((color, age)) => color.scaled(self.level(*age)),
+                None => RgbColor::default(),
+            };
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Errors returned by the BlinkStick library.
+//!
+//! Transfer failures keep the underlying `rusb::Error` or `io::Error` together
+//! with the operation and report that failed, so callers can tell a missing
+//! udev rule from an unplugged device.
+
+use crate::{BlinkStickVariant, DeviceSelector};
+use std::{fmt, io};
+use thiserror::Error;
+
+/// Result type used throughout the library
+pub type Result<T, E = BlinkStickError> = std::result::Result<T, E>;
+
+/// What the library was doing when a USB or OS call failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Enumerate,
+    ReadDescriptor,
+    Open,
+    DetachKernelDriver,
+    SetConfiguration,
+    ClaimInterface,
+    SendReport,
+    ReadReport,
+    ReadString(u8),
+    Hotplug,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enumerate => f.write_str("enumerate devices"),
+            Self::ReadDescriptor => f.write_str("read the device descriptor"),
+            Self::Open => f.write_str("open the device"),
+            Self::DetachKernelDriver => f.write_str("detach the kernel driver"),
+            Self::SetConfiguration => f.write_str("set the configuration"),
+            Self::ClaimInterface => f.write_str("claim the interface"),
+            Self::SendReport => f.write_str("send report"),
+            Self::ReadReport => f.write_str("read report"),
+            Self::ReadString(index) => write!(f, "read string descriptor {}", index),
+            Self::Hotplug => f.write_str("register for hotplug events"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlinkStickError {
+    /// A libusb call failed
+    #[error("Failed to {operation}{}: {source}", report_suffix(.report_id))]
+    Usb {
+        operation: Operation,
+        report_id: Option<u8>,
+        #[source]
+        source: rusb::Error,
+    },
+
+    /// An operating system call failed, e.g. on a hidraw node
+    #[error("Failed to {operation}{}: {source}", report_suffix(.report_id))]
+    Io {
+        operation: Operation,
+        report_id: Option<u8>,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("No BlinkStick devices found")]
+    NoDeviceFound,
+
+    #[error("No BlinkStick found with {0}")]
+    NoMatchingDevice(DeviceSelector),
+
+    #[error("{variant} does not support {operation}")]
+    Unsupported {
+        variant: BlinkStickVariant,
+        operation: &'static str,
+    },
+
+    /// An argument is outside the range the device accepts
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    /// The thread owning the device exited, e.g. after a panic
+    #[error("The BlinkStick worker thread has stopped")]
+    WorkerStopped,
+
+    /// The device answered with data that could not be decoded
+    #[error("Invalid response to {operation}{}: {message}", report_suffix(.report_id))]
+    InvalidResponse {
+        operation: Operation,
+        report_id: Option<u8>,
+        message: String,
+    },
+}
+
+fn report_suffix(report_id: &Option<u8>) -> String {
+    report_id
+        .map(|id| format!(" {:#04x}", id))
+        .unwrap_or_default()
+}
+
+impl BlinkStickError {
+    /// Build a `map_err` adapter for libusb failures
+    pub(crate) fn usb(
+        operation: Operation,
+        report_id: Option<u8>,
+    ) -> impl FnOnce(rusb::Error) -> Self {
+        move |source| Self::Usb {
+            operation,
+            report_id,
+            source,
+        }
+    }
+
+    /// Build a `map_err` adapter for operating system failures
+    #[cfg(all(feature = "hidraw", target_os = "linux"))]
+    pub(crate) fn io(
+        operation: Operation,
+        report_id: Option<u8>,
+    ) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self::Io {
+            operation,
+            report_id,
+            source,
+        }
+    }
+
+    pub(crate) fn invalid_response(
+        operation: Operation,
+        report_id: Option<u8>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::InvalidResponse {
+            operation,
+            report_id,
+            message: message.into(),
+        }
+    }
+
+    /// Copy the error for callers that share one failed transfer
+    ///
+    /// An I/O error keeps its OS error code, or its kind and message.
+    pub(crate) fn duplicate(&self) -> Self {
+        match self {
+            Self::Usb {
+                operation,
+                report_id,
+                source,
+            } => Self::Usb {
+                operation: *operation,
+                report_id: *report_id,
+                source: *source,
+            },
+            Self::Io {
+                operation,
+                report_id,
+                source,
+            } => Self::Io {
+                operation: *operation,
+                report_id: *report_id,
+                source: match source.raw_os_error() {
+                    Some(code) => io::Error::from_raw_os_error(code),
+                    None => io::Error::new(source.kind(), source.to_string()),
+                },
+            },
+            Self::NoDeviceFound => Self::NoDeviceFound,
+            Self::NoMatchingDevice(selector) => Self::NoMatchingDevice(selector.clone()),
+            Self::Unsupported { variant, operation } => Self::Unsupported {
+                variant: *variant,
+                operation,
+            },
+            Self::InvalidArgument(message) => Self::InvalidArgument(message.clone()),
+            Self::WorkerStopped => Self::WorkerStopped,
+            Self::InvalidResponse {
+                operation,
+                report_id,
+                message,
+            } => Self::InvalidResponse {
+                operation: *operation,
+                report_id: *report_id,
+                message: message.clone(),
+            },
+        }
+    }
+
+    /// The operation that failed, for transfer and response errors
+    pub fn operation(&self) -> Option<Operation> {
+        match self {
+            Self::Usb { operation, .. }
+            | Self::Io { operation, .. }
+            | Self::InvalidResponse { operation, .. } => Some(*operation),
+            _ => None,
+        }
+    }
+
+    /// The report involved in the failure, if any
+    pub fn report_id(&self) -> Option<u8> {
+        match self {
+            Self::Usb { report_id, .. }
+            | Self::Io { report_id, .. }
+            | Self::InvalidResponse { report_id, .. } => *report_id,
+            _ => None,
+        }
+    }
+
+    /// The libusb error behind the failure
+    pub fn usb_error(&self) -> Option<rusb::Error> {
+        match self {
+            Self::Usb { source, .. } => Some(*source),
+            _ => None,
+        }
+    }
+
+    /// Whether talking to the device failed, as opposed to a rejected request
+    pub fn is_transfer_error(&self) -> bool {
+        matches!(self, Self::Usb { .. } | Self::Io { .. })
+    }
+
+    /// The device exists but the current user may not access it
+    ///
+    /// Usually fixed by installing the udev rule.
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            Self::Usb { source, .. } => *source == rusb::Error::Access,
+            Self::Io { source, .. } => source.kind() == io::ErrorKind::PermissionDenied,
+            _ => false,
+        }
+    }
+
+    /// The device was unplugged or reset
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            Self::Usb { source, .. } => {
+                matches!(source, rusb::Error::NoDevice | rusb::Error::NotFound)
+            }
+            Self::Io { source, .. } => {
+                source.kind() == io::ErrorKind::NotFound || source.raw_os_error() == Some(ENODEV)
+            }
+            _ => false,
+        }
+    }
+
+    /// The failure may go away if the same request is sent again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Usb { source, .. } => matches!(
+                source,
+                rusb::Error::Timeout
+                    | rusb::Error::Pipe
+                    | rusb::Error::Busy
+                    | rusb::Error::Interrupted
+            ),
+            Self::Io { source, .. } => {
+                matches!(
+                    source.kind(),
+                    io::ErrorKind::TimedOut
+                        | io::ErrorKind::Interrupted
+                        | io::ErrorKind::WouldBlock
+                ) || source.raw_os_error() == Some(EPIPE)
+            }
+            _ => false,
+        }
+    }
+}
+
+// errno values the kernel uses for a vanished or stalled device
+const ENODEV: i32 = 19;
+const EPIPE: i32 = 32;
@@ -0,0 +1,345 @@
+//! Linux hidraw backend.
+//!
+//! Talks to `/dev/hidraw*` nodes through the HIDIOCSFEATURE/HIDIOCGFEATURE
+//! ioctls, so the kernel usbhid driver stays bound and no libusb access to the
+//! device is needed. Devices are discovered through `/sys/class/hidraw`.
+
+use crate::transport::{Transport, SERIAL_DESCRIPTOR_INDEX};
+use crate::{
+    BlinkStick, BlinkStickError, Operation, Result, BLINKSTICK_PRODUCT_ID, BLINKSTICK_VENDOR_ID,
+};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+const SYSFS_HIDRAW: &str = "/sys/class/hidraw";
+const DEV_ROOT: &str = "/dev";
+
+// _IOC(_IOC_WRITE | _IOC_READ, 'H', nr, len) with the generic ioctl layout
+const fn hid_ioc(nr: u32, len: usize) -> u32 {
+    (3 << 30) | ((len as u32) << 16) | ((b'H' as u32) << 8) | nr
+}
+
+const HIDIOCSFEATURE: u32 = 0x06;
+const HIDIOCGFEATURE: u32 = 0x07;
+
+/// Feature report access to a hidraw node
+pub trait FeatureIo {
+    /// Send a feature report. The first byte of `data` is the report ID.
+    fn set_feature(&self, data: &[u8]) -> io::Result<()>;
+
+    /// Read a feature report into `data`, whose first byte selects the report ID
+    fn get_feature(&self, data: &mut [u8]) -> io::Result<usize>;
+}
+
+/// An open `/dev/hidraw*` node
+pub struct HidrawFile {
+    file: File,
+}
+
+impl HidrawFile {
+    /// Open a hidraw node for reading and writing
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Self { file })
+    }
+}
+
+impl FeatureIo for HidrawFile {
+    fn set_feature(&self, data: &[u8]) -> io::Result<()> {
+        // SAFETY: the kernel only reads `data.len()` bytes, as encoded in the request
+        let ret = unsafe {
+            libc::ioctl(
+                self.file.as_raw_fd(),
+                hid_ioc(HIDIOCSFEATURE, data.len()) as _,
+                data.as_ptr(),
+            )
+        };
+
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    fn get_feature(&self, data: &mut [u8]) -> io::Result<usize> {
+        // SAFETY: the kernel writes at most `data.len()` bytes, as encoded in the request
+        let ret = unsafe {
+            libc::ioctl(
+                self.file.as_raw_fd(),
+                hid_ioc(HIDIOCGFEATURE, data.len()) as _,
+                data.as_mut_ptr(),
+            )
+        };
+
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(ret as usize)
+    }
+}
+
+/// A BlinkStick found under `/sys/class/hidraw`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HidrawDevice {
+    /// Device node, e.g. `/dev/hidraw3`
+    pub path: PathBuf,
+    pub serial: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// bcdDevice release number of the USB device
+    pub version: Option<u16>,
+}
+
+impl HidrawDevice {
+    /// Open the device node
+    pub fn open(&self) -> Result<HidrawTransport> {
+        let file =
+            HidrawFile::open(&self.path).map_err(BlinkStickError::io(Operation::Open, None))?;
+
+        let mut transport = HidrawTransport::new(file).with_strings(
+            self.manufacturer.clone(),
+            self.product.clone(),
+            self.serial.clone(),
+        );
+
+        if let Some(version) = self.version {
+            transport = transport.with_version(version);
+        }
+
+        Ok(transport)
+    }
+}
+
+/// Enumerate BlinkSticks exposed through hidraw
+pub fn find_all() -> Result<Vec<HidrawDevice>> {
+    find_all_in(Path::new(SYSFS_HIDRAW), Path::new(DEV_ROOT))
+}
+
+/// Enumerate BlinkSticks below an arbitrary sysfs class directory
+///
+/// `sysfs` plays the role of `/sys/class/hidraw` and `dev` that of `/dev`.
+pub fn find_all_in(sysfs: &Path, dev: &Path) -> Result<Vec<HidrawDevice>> {
+    let mut result = Vec::new();
+
+    let enumerate_error = || BlinkStickError::io(Operation::Enumerate, None);
+
+    for entry in fs::read_dir(sysfs).map_err(enumerate_error())? {
+        let entry = entry.map_err(enumerate_error())?;
+        let hid_dir = entry.path().join("device");
+
+        let uevent = match fs::read_to_string(hid_dir.join("uevent")) {
+            Ok(uevent) => uevent,
+            Err(_) => continue,
+        };
+
+        let mut ids = None;
+        let mut uniq = None;
+        let mut name = None;
+
+        for line in uevent.lines() {
+            if let Some(value) = line.strip_prefix("HID_ID=") {
+                ids = parse_hid_id(value);
+            } else if let Some(value) = line.strip_prefix("HID_UNIQ=") {
+                uniq = Some(value.to_string()).filter(|v| !v.is_empty());
+            } else if let Some(value) = line.strip_prefix("HID_NAME=") {
+                name = Some(value.to_string()).filter(|v| !v.is_empty());
+            }
+        }
+
+        if ids != Some((BLINKSTICK_VENDOR_ID, BLINKSTICK_PRODUCT_ID)) {
+            continue;
+        }
+
+        // device -> HID device, ../ -> USB interface, ../../ -> USB device
+        let usb_dir = hid_dir.join("../..");
+        let read_attr = |attr: &str| {
+            fs::read_to_string(usb_dir.join(attr))
+                .ok()
+                .map(|v| v.trim().to_string())
+        };
+
+        result.push(HidrawDevice {
+            path: dev.join(entry.file_name()),
+            serial: read_attr("serial").or(uniq),
+            manufacturer: read_attr("manufacturer"),
+            product: read_attr("product").or(name),
+            version: read_attr("bcdDevice").and_then(|v| u16::from_str_radix(&v, 16).ok()),
+        });
+    }
+
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(result)
+}
+
+/// Parse `HID_ID=bus:vendor:product` into vendor and product IDs
+fn parse_hid_id(value: &str) -> Option<(u16, u16)> {
+    let mut parts = value.split(':').skip(1);
+    // The fields are 8 hex digits wide; anything above 0xFFFF is not a USB ID
+    let vendor = u16::from_str_radix(parts.next()?, 16).ok()?;
+    let product = u16::from_str_radix(parts.next()?, 16).ok()?;
+
+    Some((vendor, product))
+}
+
+/// Transport that sends reports through hidraw feature ioctls
+pub struct HidrawTransport<D: FeatureIo = HidrawFile> {
+    io: D,
+    manufacturer: Option<String>,
+    product: Option<String>,
+    serial: Option<String>,
+    version: Option<u16>,
+}
+
+impl<D: FeatureIo> HidrawTransport<D> {
+    /// Wrap a feature report backend
+    pub fn new(io: D) -> Self {
+        Self {
+            io,
+            manufacturer: None,
+            product: None,
+            serial: None,
+            version: None,
+        }
+    }
+
+    /// Provide the strings hidraw cannot read from the device descriptor
+    pub fn with_strings(
+        mut self,
+        manufacturer: Option<String>,
+        product: Option<String>,
+        serial: Option<String>,
+    ) -> Self {
+        self.manufacturer = manufacturer;
+        self.product = product;
+        self.serial = serial;
+        self
+    }
+
+    /// Provide the bcdDevice release number used for variant detection
+    pub fn with_version(mut self, version: u16) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Access the feature report backend
+    pub fn io(&self) -> &D {
+        &self.io
+    }
+}
+
+impl<D: FeatureIo> Transport for HidrawTransport<D> {
+    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+        self.io.set_feature(data).map_err(BlinkStickError::io(
+            Operation::SendReport,
+            data.first().copied(),
+        ))
+    }
+
+    fn send_class_report(&self, w_value: u16, _w_index: u16, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Err(BlinkStickError::InvalidArgument(
+                "Class reports need at least the report ID byte".to_string(),
+            ));
+        }
+
+        // The first byte of a class report is the report ID slot
+        let mut report = data.to_vec();
+        report[0] = (w_value & 0xFF) as u8;
+
+        self.send_feature_report(&report)
+    }
+
+    fn get_feature_report(&self, data: &mut [u8]) -> Result<()> {
+        let report_id = data.first().copied();
+
+        self.io
+            .get_feature(data)
+            .map_err(BlinkStickError::io(Operation::ReadReport, report_id))?;
+
+        Ok(())
+    }
+
+    fn read_class_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> Result<usize> {
+        if data.is_empty() {
+            return Err(BlinkStickError::InvalidArgument(
+                "Class reports need at least the report ID byte".to_string(),
+            ));
+        }
+
+        let report_id = (w_value & 0xFF) as u8;
+
+        if w_index != 0 {
+            return Err(BlinkStickError::Io {
+                operation: Operation::ReadReport,
+                report_id: Some(report_id),
+                source: io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("hidraw cannot address wIndex {}", w_index),
+                ),
+            });
+        }
+
+        data[0] = report_id;
+
+        self.io
+            .get_feature(data)
+            .map_err(BlinkStickError::io(Operation::ReadReport, Some(report_id)))
+    }
+
+    fn read_string_descriptor(&self, index: u8) -> Result<String> {
+        let value = match index {
+            1 => &self.manufacturer,
+            2 => &self.product,
+            SERIAL_DESCRIPTOR_INDEX => &self.serial,
+            _ => &None,
+        };
+
+        value.clone().ok_or_else(|| BlinkStickError::Io {
+            operation: Operation::ReadString(index),
+            report_id: None,
+            source: io::Error::new(io::ErrorKind::Unsupported, "not exposed through sysfs"),
+        })
+    }
+
+    fn device_version(&self) -> Result<u16> {
+        self.version.ok_or_else(|| BlinkStickError::Io {
+            operation: Operation::ReadDescriptor,
+            report_id: None,
+            source: io::Error::new(
+                io::ErrorKind::Unsupported,
+                "bcdDevice not exposed through sysfs",
+            ),
+        })
+    }
+}
+
+impl BlinkStick<HidrawTransport> {
+    /// Find the first BlinkStick exposed through hidraw
+    pub fn find_first_hidraw() -> Result<Self> {
+        let devices = find_all()?;
+        let device = devices.first().ok_or(BlinkStickError::NoDeviceFound)?;
+
+        Ok(Self::new(device.open()?))
+    }
+
+    /// Open a BlinkStick by its hidraw device node
+    pub fn open_hidraw(path: &Path) -> Result<Self> {
+        let device = find_all()?
+            .into_iter()
+            .find(|device| device.path == path)
+            .unwrap_or_else(|| HidrawDevice {
+                path: path.to_path_buf(),
+                serial: None,
+                manufacturer: None,
+                product: None,
+                version: None,
+            });
+
+        Ok(Self::new(device.open()?))
+    }
+}
@@ -3,46 +3,65 @@
 //! A Rust interface to control BlinkStick devices connected to the computer.
 //! BlinkStick is a smart USB LED pixel. More info: https://www.blinkstick.com
 
-use anyhow::{anyhow, Result};
-use rusb::{Context, Device, DeviceHandle, UsbContext};
+use rusb::{Context, Device, UsbContext};
+use std::sync::{Mutex, MutexGuard};
 use std::time::Duration;
-use thiserror::Error;
+
+pub mod animator;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod builder;
+pub mod device_info;
+pub mod easing;
+pub mod effect;
+pub mod effects;
+pub mod error;
+#[cfg(all(feature = "hidraw", target_os = "linux"))]
+pub mod hidraw;
+pub mod mode;
+pub mod resilient;
+pub mod selector;
+pub mod shared;
+pub mod simulated;
+pub mod timeline;
+pub mod transport;
+pub mod variant;
+pub mod watcher;
+
+pub use animator::{AnimationHandle, Animator, EndState, StopError};
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncBlinkStick;
+pub use builder::{BlinkStickBuilder, TransferOptions};
+pub use device_info::DeviceInfo;
+pub use easing::Easing;
+pub use effect::{Effect, Engine};
+pub use error::{BlinkStickError, Operation, Result};
+pub use mode::Mode;
+pub use resilient::{ReconnectPolicy, ResilientBlinkStick};
+pub use selector::{DeviceCandidate, DevicePath, DeviceSelector};
+pub use shared::SharedBlinkStick;
+pub use simulated::SimulatedBlinkStick;
+pub use timeline::{Keyframe, Repeat, Timeline};
+pub use transport::{Transport, UsbTransport};
+pub use variant::BlinkStickVariant;
+pub use watcher::{DeviceEvent, DeviceKey, DeviceWatcher};
 
 // BlinkStick USB identifiers
-const BLINKSTICK_VENDOR_ID: u16 = 0x20A0;
-const BLINKSTICK_PRODUCT_ID: u16 = 0x41E5;
+pub(crate) const BLINKSTICK_VENDOR_ID: u16 = 0x20A0;
+pub(crate) const BLINKSTICK_PRODUCT_ID: u16 = 0x41E5;
 
 // BlinkStick report IDs
 const REPORT_ID_1: u8 = 1; // First LED for BlinkStick
-const REPORT_ID_2: u8 = 2; // 8 LEDs for BlinkStick Pro
+const REPORT_ID_INFO_BLOCK_1: u8 = 2; // Device name
+const REPORT_ID_INFO_BLOCK_2: u8 = 3; // Arbitrary user data
+const REPORT_ID_MODE: u8 = 4; // Output mode
+const REPORT_ID_INDEXED: u8 = 5; // Single LED by channel and index
+const REPORT_ID_LED_COUNT: u8 = 0x81; // Number of LEDs on a channel
+const INFO_BLOCK_SIZE: usize = 32;
 const MAX_LED_COUNT: usize = 64;
 const LED_DATA_REPORTS: &[(u16, usize)] = &[(6, 8), (7, 16), (8, 32), (9, 64)];
 
-#[derive(Debug, Error)]
-pub enum BlinkStickError {
-    #[error("USB error: {0}")]
-    UsbError(#[from] rusb::Error),
-
-    #[error("No BlinkStick devices found")]
-    NoDeviceFound,
-
-    #[error("Failed to get device descriptor")]
-    DeviceDescriptorError,
-
-    #[error("Failed to open device")]
-    OpenDeviceError,
-
-    #[error("Failed to claim interface")]
-    ClaimInterfaceError,
-
-    #[error("Failed to set configuration")]
-    SetConfigurationError,
-
-    #[error("Failed to send control transfer")]
-    ControlTransferError,
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct RgbColor {
     pub r: u8,
     pub g: u8,
@@ -210,6 +229,58 @@ impl RgbColor {
         }
     }
 
+    /// Scale every component by `factor`, clamped to 0.0..=1.0
+    pub fn scaled(&self, factor: f32) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+
+        Self {
+            r: (self.r as f32 * factor) as u8,
+            g: (self.g as f32 * factor) as u8,
+            b: (self.b as f32 * factor) as u8,
+        }
+    }
+
+    /// Blend towards `to`; `t` of 0.0 gives `self` and 1.0 gives `to`
+    pub fn lerp(&self, to: &RgbColor, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+
+        Self {
+            r: mix(self.r, to.r),
+            g: mix(self.g, to.g),
+            b: mix(self.b, to.b),
+        }
+    }
+
+    /// Convert a hue in degrees, saturation and value to RGB
+    ///
+    /// The hue wraps around at 360; saturation and value are clamped to
+    /// 0.0..=1.0.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+
+        Self {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+        }
+    }
+
     pub fn from_hex(hex: &str) -> Option<Self> {
         let hex = hex.trim_start_matches('#');
 
@@ -227,21 +298,44 @@ impl RgbColor {
     }
 }
 
-pub struct BlinkStick {
-    handle: DeviceHandle<Context>,
+pub struct BlinkStick<T: Transport = UsbTransport> {
+    // Only `None` after `into_transport`
+    transport: Option<T>,
+    variant: BlinkStickVariant,
+    options: TransferOptions,
+    turn_off_on_close: bool,
+    // Last colors successfully written to each channel
+    frames: Mutex<Vec<Vec<RgbColor>>>,
+    // Most LEDs ever written on each channel, even by earlier frames
+    widest: Mutex<Vec<usize>>,
 }
 
-impl BlinkStick {
+impl BlinkStick<UsbTransport> {
+    /// Configure timeouts, retries and device selection before opening
+    pub fn builder() -> BlinkStickBuilder {
+        BlinkStickBuilder::new()
+    }
+
     /// Find all connected BlinkStick devices
-    pub fn find_all() -> Result<Vec<Device<Context>>> {
-        let context = Context::new()?;
-        let devices = context.devices()?;
+    pub fn find_all() -> Result<Vec<DeviceInfo>> {
+        Self::find_devices()?
+            .iter()
+            .map(DeviceInfo::from_device)
+            .collect()
+    }
+
+    /// Find the libusb devices of all connected BlinkSticks
+    pub fn find_devices() -> Result<Vec<Device<Context>>> {
+        let context = Context::new().map_err(BlinkStickError::usb(Operation::Enumerate, None))?;
+        let devices = context
+            .devices()
+            .map_err(BlinkStickError::usb(Operation::Enumerate, None))?;
         let mut result = Vec::new();
 
         for device in devices.iter() {
             let device_desc = device
                 .device_descriptor()
-                .map_err(|_| BlinkStickError::DeviceDescriptorError)?;
+                .map_err(BlinkStickError::usb(Operation::ReadDescriptor, None))?;
 
             if device_desc.vendor_id() == BLINKSTICK_VENDOR_ID
                 && device_desc.product_id() == BLINKSTICK_PRODUCT_ID
@@ -255,51 +349,196 @@ impl BlinkStick {
 
     /// Find the first available BlinkStick device
     pub fn find_first() -> Result<Self> {
-        let devices = Self::find_all()?;
+        Self::open_selected(&DeviceSelector::First)
+    }
+
+    /// Open the BlinkStick with the given serial number
+    pub fn open_by_serial(serial: &str) -> Result<Self> {
+        Self::open_selected(&DeviceSelector::Serial(serial.to_string()))
+    }
+
+    /// Open the BlinkStick whose info block 1 holds the given name
+    pub fn open_by_name(name: &str) -> Result<Self> {
+        Self::open_selected(&DeviceSelector::Name(name.to_string()))
+    }
+
+    /// Open the BlinkStick plugged into the given bus and port chain
+    pub fn open_by_path(path: &DevicePath) -> Result<Self> {
+        Self::open_selected(&DeviceSelector::Path(path.clone()))
+    }
+
+    /// Open the first BlinkStick matching a selector
+    ///
+    /// Selecting by name has to open each device in turn to read info block
+    /// 1. The handle of the matching device is kept rather than opened again.
+    pub fn open_selected(selector: &DeviceSelector) -> Result<Self> {
+        let devices = Self::find_devices()?;
 
         if devices.is_empty() {
-            return Err(BlinkStickError::NoDeviceFound.into());
+            return Err(BlinkStickError::NoDeviceFound);
         }
 
-        Self::open(devices[0].clone())
+        if let DeviceSelector::Name(name) = selector {
+            return devices
+                .into_iter()
+                .filter_map(|device| Self::open(device).ok())
+                .find(|blinkstick| blinkstick.name().ok().as_deref() == Some(name.as_str()))
+                .ok_or_else(|| BlinkStickError::NoMatchingDevice(selector.clone()));
+        }
+
+        for device in devices {
+            if selector.matches(&DeviceInfo::from_device(&device)?) {
+                return Self::open(device);
+            }
+        }
+
+        Err(BlinkStickError::NoMatchingDevice(selector.clone()))
     }
 
     /// Open a specific BlinkStick device
     pub fn open(device: Device<Context>) -> Result<Self> {
         let handle = device
             .open()
-            .map_err(|_| BlinkStickError::OpenDeviceError)?;
+            .map_err(BlinkStickError::usb(Operation::Open, None))?;
+
+        let detach = handle
+            .kernel_driver_active(0)
+            .map_err(BlinkStickError::usb(Operation::DetachKernelDriver, None))?;
 
-        if handle.kernel_driver_active(0)? {
-            handle.detach_kernel_driver(0)?;
+        if detach {
+            handle
+                .detach_kernel_driver(0)
+                .map_err(BlinkStickError::usb(Operation::DetachKernelDriver, None))?;
         }
 
-        handle
+        // From here on dropping the transport hands the interface back to the kernel
+        let transport = UsbTransport::new(handle).with_detached_kernel_driver(detach);
+
+        transport
+            .handle()
             .set_active_configuration(1)
-            .map_err(|_| BlinkStickError::SetConfigurationError)?;
-        handle
+            .map_err(BlinkStickError::usb(Operation::SetConfiguration, None))?;
+        transport
+            .handle()
             .claim_interface(0)
-            .map_err(|_| BlinkStickError::ClaimInterfaceError)?;
+            .map_err(BlinkStickError::usb(Operation::ClaimInterface, None))?;
 
-        Ok(Self { handle })
+        Ok(Self::new(transport))
+    }
+}
+
+impl<T: Transport> BlinkStick<T> {
+    /// Drive a BlinkStick through an arbitrary transport
+    ///
+    /// The hardware variant is detected from the serial number and release
+    /// number. If either cannot be read the variant is `Unknown` and no
+    /// operation is rejected up front.
+    pub fn new(transport: T) -> Self {
+        let variant = match (
+            transport.read_string_descriptor(transport::SERIAL_DESCRIPTOR_INDEX),
+            transport.device_version(),
+        ) {
+            (Ok(serial), Ok(version)) => BlinkStickVariant::detect(&serial, version),
+            _ => BlinkStickVariant::Unknown,
+        };
+
+        Self {
+            transport: Some(transport),
+            variant,
+            options: TransferOptions::default(),
+            turn_off_on_close: false,
+            frames: Mutex::new(Vec::new()),
+            widest: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Override the detected hardware variant
+    pub fn with_variant(mut self, variant: BlinkStickVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Apply transfer timeout and retry settings
+    pub fn with_options(mut self, options: TransferOptions) -> Self {
+        if let Some(transport) = &mut self.transport {
+            transport.set_timeout(options.timeout);
+        }
+
+        self.options = options;
+        self
+    }
+
+    /// The transfer settings in use
+    pub fn options(&self) -> &TransferOptions {
+        &self.options
+    }
+
+    /// Blank every LED this handle lit when it is dropped
+    ///
+    /// Useful for tools that borrow the stick briefly and should not leave it
+    /// glowing. Errors while blanking are ignored.
+    pub fn with_turn_off_on_close(mut self, turn_off: bool) -> Self {
+        self.turn_off_on_close = turn_off;
+        self
+    }
+
+    /// The hardware variant of the device
+    pub fn variant(&self) -> BlinkStickVariant {
+        self.variant
+    }
+
+    /// Access the underlying transport
+    pub fn transport(&self) -> &T {
+        self.transport
+            .as_ref()
+            .expect("transport is only taken by into_transport")
+    }
+
+    /// Consume the BlinkStick and return its transport
+    ///
+    /// The LEDs are left as they are, even with `with_turn_off_on_close`.
+    pub fn into_transport(mut self) -> T {
+        self.transport
+            .take()
+            .expect("transport is only taken by into_transport")
     }
 
     /// Set the color of the first LED
     pub fn set_color(&self, color: &RgbColor) -> Result<()> {
         let data = [REPORT_ID_1, color.r, color.g, color.b];
-        self.send_feature_report(&data)
+        self.send_feature_report(&data)?;
+
+        self.record_led(0, 0, color);
+        Ok(())
     }
 
-    /// Set the color of a specific LED for BlinkStick Pro
+    /// Set the color of a specific LED on channel 0
     pub fn set_color_indexed(&self, index: u8, color: &RgbColor) -> Result<()> {
-        if index == 0 {
+        self.set_color_at(0, index, color)
+    }
+
+    /// Set the color of a single LED on a channel
+    pub fn set_color_at(&self, channel: u8, index: u8, color: &RgbColor) -> Result<()> {
+        if channel == 0 && index == 0 {
             // For the first LED, use report ID 1
             return self.set_color(color);
         }
 
-        // For other LEDs, use report ID 2
-        let data = [REPORT_ID_2, index, color.g, color.r, color.b];
-        self.send_feature_report(&data)
+        self.check_channel(channel)?;
+        self.require(self.variant.supports_indexed_color(), "indexed colors")?;
+
+        if index as usize >= MAX_LED_COUNT {
+            return Err(BlinkStickError::InvalidArgument(format!(
+                "LED index must be below {} (got {})",
+                MAX_LED_COUNT, index
+            )));
+        }
+
+        let data = [REPORT_ID_INDEXED, channel, index, color.r, color.g, color.b];
+        self.send_feature_report(&data)?;
+
+        self.record_led(channel, index, color);
+        Ok(())
     }
 
     /// Set colors for multiple LEDs at once
@@ -308,20 +547,22 @@ impl BlinkStick {
             return Ok(());
         }
 
-        if channel > 2 {
-            anyhow::bail!("Channel must be 0, 1, or 2");
-        }
+        self.check_channel(channel)?;
 
-        if leds.len() == 1 {
-            return self.set_color(&leds[0]);
+        if leds.len() == 1 && channel == 0 {
+            self.set_color(&leds[0])?;
+            self.record_frame(channel, leds);
+            return Ok(());
         }
 
+        self.require(self.variant.supports_multi_led(), "multi-LED reports")?;
+
         if leds.len() > MAX_LED_COUNT {
-            anyhow::bail!(
+            return Err(BlinkStickError::InvalidArgument(format!(
                 "BlinkStick supports up to {} LEDs per channel, got {}",
                 MAX_LED_COUNT,
                 leds.len()
-            );
+            )));
         }
 
         let (report_id, capacity) = Self::determine_led_report(leds.len());
@@ -340,7 +581,68 @@ impl BlinkStick {
             payload.extend_from_slice(&[0, 0, 0]);
         }
 
-        self.send_class_report(report_id, 0, &payload)
+        self.send_class_report(report_id, 0, &payload)?;
+
+        self.record_frame(channel, leds);
+        Ok(())
+    }
+
+    /// The colors last written to a channel, if anything was written
+    ///
+    /// This reflects what this handle sent, not what the device reports.
+    pub fn last_frame(&self, channel: u8) -> Option<Vec<RgbColor>> {
+        self.frames()
+            .get(channel as usize)
+            .filter(|frame| !frame.is_empty())
+            .cloned()
+    }
+
+    /// The colors last written to every channel, indexed by channel
+    pub fn last_frames(&self) -> Vec<Vec<RgbColor>> {
+        self.frames().clone()
+    }
+
+    fn frames(&self) -> MutexGuard<'_, Vec<Vec<RgbColor>>> {
+        self.frames.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record_frame(&self, channel: u8, leds: &[RgbColor]) {
+        let mut frames = self.frames();
+
+        if frames.len() <= channel as usize {
+            frames.resize(channel as usize + 1, Vec::new());
+        }
+
+        frames[channel as usize] = leds.to_vec();
+        self.record_extent(channel, leds.len());
+    }
+
+    fn record_led(&self, channel: u8, index: u8, color: &RgbColor) {
+        let mut frames = self.frames();
+
+        if frames.len() <= channel as usize {
+            frames.resize(channel as usize + 1, Vec::new());
+        }
+
+        let frame = &mut frames[channel as usize];
+
+        if frame.len() <= index as usize {
+            frame.resize(index as usize + 1, RgbColor::default());
+        }
+
+        frame[index as usize] = color.clone();
+        self.record_extent(channel, frame.len());
+    }
+
+    fn record_extent(&self, channel: u8, led_count: usize) {
+        let mut widest = self.widest.lock().unwrap_or_else(|e| e.into_inner());
+
+        if widest.len() <= channel as usize {
+            widest.resize(channel as usize + 1, 0);
+        }
+
+        let extent = &mut widest[channel as usize];
+        *extent = (*extent).max(led_count);
     }
 
     fn determine_led_report(led_count: usize) -> (u16, usize) {
@@ -357,31 +659,44 @@ impl BlinkStick {
     }
 
     /// Attempt to read the number of LEDs available on a given channel
+    ///
+    /// Models without the LED count report return their factory LED count.
     pub fn get_led_count(&self, channel: u8) -> Result<u8> {
-        if channel > 2 {
-            anyhow::bail!("Channel must be 0, 1, or 2");
+        self.check_channel(channel)?;
+
+        if !self.variant.supports_led_count() {
+            return Ok(self.variant.default_led_count());
         }
 
         let mut data = [0u8; 2];
-        let transferred = match self.read_class_report(0x81, channel as u16, &mut data) {
-            Ok(len) => len,
-            Err(err) => {
-                if channel != 0 {
-                    self.read_class_report(0x81, 0, &mut data)?
-                } else {
-                    return Err(err);
+        let transferred =
+            match self.read_class_report(REPORT_ID_LED_COUNT as u16, channel as u16, &mut data) {
+                Ok(len) => len,
+                Err(err) => {
+                    if channel != 0 {
+                        self.read_class_report(REPORT_ID_LED_COUNT as u16, 0, &mut data)?
+                    } else {
+                        return Err(err);
+                    }
                 }
-            }
-        };
+            };
 
         if transferred < 2 {
-            anyhow::bail!("Failed to read LED count from device");
+            return Err(BlinkStickError::invalid_response(
+                Operation::ReadReport,
+                Some(REPORT_ID_LED_COUNT),
+                format!("expected 2 bytes, got {}", transferred),
+            ));
         }
 
         let count = data[1];
 
         if count == 0 {
-            Err(anyhow!("Device reported zero LEDs on channel {}", channel))
+            Err(BlinkStickError::invalid_response(
+                Operation::ReadReport,
+                Some(REPORT_ID_LED_COUNT),
+                format!("zero LEDs on channel {}", channel),
+            ))
         } else {
             Ok(count)
         }
@@ -397,45 +712,59 @@ impl BlinkStick {
         self.set_colors(channel, &leds)
     }
 
+    /// Switch off every LED written through this handle
+    ///
+    /// Channels are blanked up to the longest frame sent to them. Channels that
+    /// were never written are left alone.
+    pub fn turn_off(&self) -> Result<()> {
+        let widest = self
+            .widest
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+
+        for (channel, led_count) in widest.into_iter().enumerate() {
+            self.set_all_colors(channel as u8, led_count as u8, &RgbColor::default())?;
+        }
+
+        Ok(())
+    }
+
     /// Configure the number of LEDs attached to the device
     pub fn set_led_count(&self, count: u8) -> Result<()> {
+        self.require(self.variant.supports_led_count(), "setting the LED count")?;
+
         if count == 0 || count as usize > MAX_LED_COUNT {
-            anyhow::bail!(
+            return Err(BlinkStickError::InvalidArgument(format!(
                 "LED count must be between 1 and {} (got {})",
-                MAX_LED_COUNT,
-                count
-            );
+                MAX_LED_COUNT, count
+            )));
         }
 
-        let payload = [0x81, count];
-        self.send_class_report(0x81, 0, &payload)
+        let payload = [REPORT_ID_LED_COUNT, count];
+        self.send_class_report(REPORT_ID_LED_COUNT as u16, 0, &payload)
     }
 
     /// Create a pulse effect
+    ///
+    /// Fades the first LED up to `color` and back down in `steps` steps each
+    /// way, leaving it off.
     pub fn pulse(&self, color: &RgbColor, duration_ms: u32, steps: u32) -> Result<()> {
-        let step_delay = Duration::from_millis((duration_ms / steps) as u64);
-
-        // Fade in
-        for i in 0..steps {
-            let factor = i as f32 / steps as f32;
-            let fade_color = RgbColor {
-                r: (color.r as f32 * factor) as u8,
-                g: (color.g as f32 * factor) as u8,
-                b: (color.b as f32 * factor) as u8,
-            };
-            self.set_color(&fade_color)?;
-            std::thread::sleep(step_delay);
-        }
+        self.pulse_with(color, duration_ms, steps, Easing::Linear)
+    }
 
-        // Fade out
-        for i in 0..steps {
-            let factor = 1.0 - (i as f32 / steps as f32);
-            let fade_color = RgbColor {
-                r: (color.r as f32 * factor) as u8,
-                g: (color.g as f32 * factor) as u8,
-                b: (color.b as f32 * factor) as u8,
-            };
-            self.set_color(&fade_color)?;
+    /// Create a pulse effect whose brightness follows `easing`
+    pub fn pulse_with(
+        &self,
+        color: &RgbColor,
+        duration_ms: u32,
+        steps: u32,
+        easing: Easing,
+    ) -> Result<()> {
+        let (step_delay, levels) = pulse_levels(duration_ms, steps, easing)?;
+
+        for factor in levels {
+            self.set_color(&color.scaled(factor))?;
             std::thread::sleep(step_delay);
         }
 
@@ -443,6 +772,9 @@ impl BlinkStick {
     }
 
     /// Get the current color of the first LED
+    ///
+    /// Report 1 carries the color as R, G, B, the same order `set_color`
+    /// writes it. Only the multi-LED reports use G, R, B.
     pub fn get_color(&self) -> Result<RgbColor> {
         let mut data = [0u8; 4];
         data[0] = REPORT_ID_1;
@@ -450,130 +782,238 @@ impl BlinkStick {
         self.get_feature_report(&mut data)?;
 
         Ok(RgbColor {
-            r: data[2],
-            g: data[1],
+            r: data[1],
+            g: data[2],
             b: data[3],
         })
     }
 
+    /// Read back the colors of the first `count` LEDs on a channel
+    ///
+    /// The firmware answers the multi-LED report `set_colors` would use for
+    /// `count` LEDs with the G, R, B data it holds.
+    pub fn get_colors(&self, channel: u8, count: usize) -> Result<Vec<RgbColor>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        self.require(self.variant.supports_multi_led(), "multi-LED reports")?;
+
+        if count > MAX_LED_COUNT {
+            return Err(BlinkStickError::InvalidArgument(format!(
+                "BlinkStick supports up to {} LEDs per channel, got {}",
+                MAX_LED_COUNT, count
+            )));
+        }
+
+        let (report_id, capacity) = Self::determine_led_report(count);
+        let mut data = vec![0u8; 2 + capacity * 3];
+        let transferred = self.read_class_report(report_id, channel as u16, &mut data)?;
+
+        if transferred < 2 + count * 3 {
+            return Err(BlinkStickError::invalid_response(
+                Operation::ReadReport,
+                Some(report_id as u8),
+                format!("expected {} bytes, got {}", 2 + count * 3, transferred),
+            ));
+        }
+
+        Ok(data[2..2 + count * 3]
+            .chunks_exact(3)
+            .map(|grb| RgbColor::new(grb[1], grb[0], grb[2]))
+            .collect())
+    }
+
     /// Get the device serial number
     pub fn get_serial(&self) -> Result<String> {
-        let mut data = [0u8; 256];
+        self.with_retries(|| {
+            self.transport()
+                .read_string_descriptor(transport::SERIAL_DESCRIPTOR_INDEX)
+        })
+    }
 
-        let len = self.handle.read_control(
-            rusb::request_type(
-                rusb::Direction::In,
-                rusb::RequestType::Standard,
-                rusb::Recipient::Device,
-            ),
-            rusb::constants::LIBUSB_REQUEST_GET_DESCRIPTOR,
-            (rusb::constants::LIBUSB_DT_STRING as u16) << 8 | 3,
-            0,
-            &mut data,
-            Duration::from_secs(1),
-        )?;
+    /// Read the first info block, conventionally holding the device name
+    pub fn get_info_block1(&self) -> Result<Vec<u8>> {
+        self.get_info_block(REPORT_ID_INFO_BLOCK_1)
+    }
 
-        if len <= 2 {
-            return Err(anyhow!("Failed to get serial number"));
-        }
+    /// Read the second info block
+    pub fn get_info_block2(&self) -> Result<Vec<u8>> {
+        self.get_info_block(REPORT_ID_INFO_BLOCK_2)
+    }
 
-        // Convert UTF-16LE to String
-        let utf16_chars: Vec<u16> = data[2..len]
-            .chunks_exact(2)
-            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-            .collect();
+    /// Write up to 32 bytes into the first info block
+    pub fn set_info_block1(&self, data: &[u8]) -> Result<()> {
+        self.set_info_block(REPORT_ID_INFO_BLOCK_1, data)
+    }
 
-        String::from_utf16(&utf16_chars)
-            .map_err(|e| anyhow!("Failed to decode serial number: {}", e))
+    /// Write up to 32 bytes into the second info block
+    pub fn set_info_block2(&self, data: &[u8]) -> Result<()> {
+        self.set_info_block(REPORT_ID_INFO_BLOCK_2, data)
     }
 
-    /// Helper function to send feature reports
-    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
-        self.handle
-            .write_control(
-                rusb::request_type(
-                    rusb::Direction::Out,
-                    rusb::RequestType::Class,
-                    rusb::Recipient::Interface,
-                ),
-                0x09,                    // SET_REPORT
-                0x0300 | data[0] as u16, // HID_REPORT_TYPE_FEATURE | report_id
-                0,
-                data,
-                Duration::from_secs(1),
+    /// Get the device name stored in the first info block
+    pub fn name(&self) -> Result<String> {
+        let block = self.get_info_block1()?;
+        let end = block.iter().position(|&b| b == 0).unwrap_or(block.len());
+
+        String::from_utf8(block[..end].to_vec()).map_err(|e| {
+            BlinkStickError::invalid_response(
+                Operation::ReadReport,
+                Some(REPORT_ID_INFO_BLOCK_1),
+                format!("device name is not UTF-8: {}", e),
             )
-            .map_err(|_| BlinkStickError::ControlTransferError)?;
+        })
+    }
 
-        Ok(())
+    /// Store a device name in the first info block
+    pub fn set_name(&self, name: &str) -> Result<()> {
+        self.set_info_block1(name.as_bytes())
     }
 
-    fn send_class_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> Result<()> {
-        self.handle
-            .write_control(
-                rusb::request_type(
-                    rusb::Direction::Out,
-                    rusb::RequestType::Class,
-                    rusb::Recipient::Interface,
-                ),
-                0x09,
-                w_value,
-                w_index,
-                data,
-                Duration::from_secs(1),
+    /// Read the output mode of the device
+    pub fn get_mode(&self) -> Result<Mode> {
+        self.require(self.variant.supports_mode(), "output modes")?;
+
+        let mut data = [0u8; 2];
+        data[0] = REPORT_ID_MODE;
+
+        self.get_feature_report(&mut data)?;
+
+        Mode::from_byte(data[1]).ok_or_else(|| {
+            BlinkStickError::invalid_response(
+                Operation::ReadReport,
+                Some(REPORT_ID_MODE),
+                format!("unknown mode {}", data[1]),
             )
-            .map_err(|_| BlinkStickError::ControlTransferError)?;
+        })
+    }
+
+    /// Switch the output mode of the device
+    pub fn set_mode(&self, mode: Mode) -> Result<()> {
+        self.require(self.variant.supports_mode(), "output modes")?;
+
+        let data = [REPORT_ID_MODE, mode.as_byte()];
+        self.send_feature_report(&data)
+    }
+
+    fn get_info_block(&self, report_id: u8) -> Result<Vec<u8>> {
+        let mut data = [0u8; INFO_BLOCK_SIZE + 1];
+        data[0] = report_id;
+
+        self.get_feature_report(&mut data)?;
+
+        Ok(data[1..].to_vec())
+    }
+
+    fn set_info_block(&self, report_id: u8, block: &[u8]) -> Result<()> {
+        if block.len() > INFO_BLOCK_SIZE {
+            return Err(BlinkStickError::InvalidArgument(format!(
+                "Info blocks hold up to {} bytes, got {}",
+                INFO_BLOCK_SIZE,
+                block.len()
+            )));
+        }
+
+        let mut data = [0u8; INFO_BLOCK_SIZE + 1];
+        data[0] = report_id;
+        data[1..=block.len()].copy_from_slice(block);
+
+        self.send_feature_report(&data)
+    }
+
+    fn check_channel(&self, channel: u8) -> Result<()> {
+        let channels = self.variant.channel_count();
+
+        if channel >= channels {
+            return Err(BlinkStickError::InvalidArgument(format!(
+                "{} has {} channel(s), channel {} is out of range",
+                self.variant, channels, channel
+            )));
+        }
 
         Ok(())
     }
 
-    /// Helper function to get feature reports
-    fn get_feature_report(&self, data: &mut [u8]) -> Result<()> {
-        let report_id = data[0];
-
-        self.handle
-            .read_control(
-                rusb::request_type(
-                    rusb::Direction::In,
-                    rusb::RequestType::Class,
-                    rusb::Recipient::Interface,
-                ),
-                0x01,                      // GET_REPORT
-                0x0300 | report_id as u16, // HID_REPORT_TYPE_FEATURE | report_id
-                0,
-                data,
-                Duration::from_secs(1),
-            )
-            .map_err(|_| BlinkStickError::ControlTransferError)?;
+    fn require(&self, supported: bool, operation: &'static str) -> Result<()> {
+        if !supported {
+            return Err(BlinkStickError::Unsupported {
+                variant: self.variant,
+                operation,
+            });
+        }
 
         Ok(())
     }
 
+    /// Repeat a transfer while it fails transiently, up to the configured retries
+    fn with_retries<R>(&self, mut transfer: impl FnMut() -> Result<R>) -> Result<R> {
+        let mut attempt = 0;
+
+        loop {
+            match transfer() {
+                Err(err) if err.is_transient() && attempt < self.options.retries => {
+                    attempt += 1;
+                    std::thread::sleep(self.options.retry_delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+        self.with_retries(|| self.transport().send_feature_report(data))
+    }
+
+    fn send_class_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> Result<()> {
+        self.with_retries(|| self.transport().send_class_report(w_value, w_index, data))
+    }
+
+    fn get_feature_report(&self, data: &mut [u8]) -> Result<()> {
+        self.with_retries(|| self.transport().get_feature_report(data))
+    }
+
     fn read_class_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> Result<usize> {
-        self.handle
-            .read_control(
-                rusb::request_type(
-                    rusb::Direction::In,
-                    rusb::RequestType::Class,
-                    rusb::Recipient::Interface,
-                ),
-                0x01,
-                w_value,
-                w_index,
-                data,
-                Duration::from_secs(1),
-            )
-            .map_err(|_| BlinkStickError::ControlTransferError.into())
+        self.with_retries(|| self.transport().read_class_report(w_value, w_index, data))
+    }
+}
+
+/// Brightness of each pulse step and the pause after each one
+///
+/// Fades in over `steps` steps ending at full brightness, then out over as
+/// many ending off.
+pub(crate) fn pulse_levels(
+    duration_ms: u32,
+    steps: u32,
+    easing: Easing,
+) -> Result<(Duration, impl Iterator<Item = f32>)> {
+    if steps == 0 {
+        return Err(BlinkStickError::InvalidArgument(
+            "pulse needs at least one step".to_string(),
+        ));
     }
+
+    let step_delay = Duration::from_millis((duration_ms / steps) as u64);
+    let fade_in = (1..=steps).map(move |i| i as f32 / steps as f32);
+    let fade_out = (1..=steps).map(move |i| 1.0 - i as f32 / steps as f32);
+
+    let levels = fade_in
+        .chain(fade_out)
+        .map(move |level| easing.apply(level));
+
+    Ok((step_delay, levels))
 }
 
-impl Drop for BlinkStick {
+impl<T: Transport> Drop for BlinkStick<T> {
     fn drop(&mut self) {
-        let _ = self.handle.release_interface(0);
+        if self.turn_off_on_close && self.transport.is_some() {
+            let _ = self.turn_off();
+        }
     }
 }
 
 /// Helper function to retrieve all connected BlinkStick devices
-pub fn find_blinksticks() -> Result<Vec<Device<Context>>> {
+pub fn find_blinksticks() -> Result<Vec<DeviceInfo>> {
     BlinkStick::find_all()
 }
 
@@ -1,18 +1,65 @@
 use anyhow::{Context, Result};
-use blinkstick::{BlinkStick, RgbColor};
+use blinkstick::effects::{Blink, BlinkOptions, BlinkTarget, Direction, Rainbow};
+use blinkstick::{
+    BlinkStick, BlinkStickBuilder, DeviceEvent, DeviceInfo, DevicePath, DeviceSelector,
+    DeviceWatcher, Easing, Engine, Mode, RgbColor,
+};
 use clap::{Parser, Subcommand};
 use std::fs::File;
 use std::io::Write;
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "blinkstick")]
 #[command(about = "Control BlinkStick devices", long_about = None)]
 struct Cli {
+    /// Select the device by serial number
+    #[arg(long, global = true, conflicts_with_all = ["name", "device"])]
+    serial: Option<String>,
+
+    /// Select the device by the name stored in info block 1
+    #[arg(long, global = true, conflicts_with_all = ["serial", "device"])]
+    name: Option<String>,
+
+    /// Select the device by USB bus and port chain, e.g. 1-2.3
+    #[arg(long, global = true, conflicts_with_all = ["serial", "name"])]
+    device: Option<DevicePath>,
+
+    /// Timeout for each USB transfer in milliseconds
+    #[arg(long, global = true, default_value_t = 1000)]
+    timeout: u64,
+
+    /// Retry transfers that fail transiently (stall, busy, timeout) this many times
+    #[arg(long, global = true, default_value_t = 0)]
+    retries: u32,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+impl Cli {
+    fn selector(&self) -> DeviceSelector {
+        if let Some(serial) = &self.serial {
+            DeviceSelector::Serial(serial.clone())
+        } else if let Some(name) = &self.name {
+            DeviceSelector::Name(name.clone())
+        } else if let Some(path) = &self.device {
+            DeviceSelector::Path(path.clone())
+        } else {
+            DeviceSelector::First
+        }
+    }
+
+    fn builder(&self) -> BlinkStickBuilder {
+        BlinkStick::builder()
+            .selector(self.selector())
+            .timeout(Duration::from_millis(self.timeout))
+            .retries(self.retries)
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Set color of BlinkStick device
@@ -51,10 +98,124 @@ enum Commands {
         /// Number of steps in the pulse
         #[arg(short, long, default_value = "20")]
         steps: u32,
+
+        /// Brightness curve: linear, quadratic, cubic, sine, exponential, bounce,
+        /// elastic or cubic-bezier(x1,y1,x2,y2)
+        #[arg(short, long, default_value_t = Easing::Linear)]
+        easing: Easing,
+    },
+
+    /// Fade smoothly from the current color to a new one
+    #[command(arg_required_else_help = true)]
+    Morph {
+        /// Color name (red, green, blue, etc.) or hex value (#FF0000)
+        color: String,
+
+        /// Duration of the transition in milliseconds
+        #[arg(short, long, default_value = "1000")]
+        duration: u64,
+
+        /// Transition curve: linear, quadratic, cubic, sine, exponential, bounce,
+        /// elastic or cubic-bezier(x1,y1,x2,y2)
+        #[arg(short, long, default_value_t = Easing::Linear)]
+        easing: Easing,
+    },
+
+    /// Blink a color a number of times
+    #[command(arg_required_else_help = true)]
+    Blink {
+        /// Color name (red, green, blue, etc.) or hex value (#FF0000)
+        color: String,
+
+        /// Number of blinks
+        #[arg(short = 'n', long, default_value_t = 1)]
+        times: u32,
+
+        /// Time the LEDs stay lit in milliseconds
+        #[arg(long, default_value_t = 250, conflicts_with_all = ["period", "duty"])]
+        on: u64,
+
+        /// Time the LEDs stay dark in milliseconds
+        #[arg(long, default_value_t = 250, conflicts_with_all = ["period", "duty"])]
+        off: u64,
+
+        /// Length of one blink in milliseconds, split by --duty
+        #[arg(long, requires = "duty")]
+        period: Option<u64>,
+
+        /// Percentage of each period the LEDs stay lit
+        #[arg(long, requires = "period", value_parser = clap::value_parser!(u8).range(0..=100))]
+        duty: Option<u8>,
+
+        /// LED index (defaults to 0 when no other option is provided)
+        #[arg(short, long, conflicts_with_all = ["range", "all"])]
+        index: Option<u8>,
+
+        /// Inclusive range of LED indexes, e.g. 2-5
+        #[arg(long, value_parser = parse_led_range, conflicts_with_all = ["index", "all"])]
+        range: Option<Range<u8>>,
+
+        /// Blink every LED on the selected channel
+        #[arg(long, conflicts_with_all = ["index", "range"])]
+        all: bool,
+
+        /// Channel to target (defaults to 0)
+        #[arg(short = 'c', long, default_value_t = 0)]
+        channel: u8,
+
+        /// Show the previous colors afterwards instead of turning the LEDs off
+        #[arg(long)]
+        restore: bool,
+    },
+
+    /// Run a rainbow across the LEDs of a channel
+    Rainbow {
+        /// Spread the color wheel along the strip and let it travel
+        #[arg(short, long)]
+        moving: bool,
+
+        /// Time for one trip around the color wheel in milliseconds
+        #[arg(short, long, default_value_t = 3000)]
+        period: u64,
+
+        /// Color saturation in percent
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+        saturation: u8,
+
+        /// Brightness in percent
+        #[arg(short, long, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+        brightness: u8,
+
+        /// Run the colors the other way
+        #[arg(short, long)]
+        reverse: bool,
+
+        /// Stop after this many milliseconds instead of running until interrupted
+        #[arg(short, long)]
+        duration: Option<u64>,
+
+        /// Number of LEDs on the channel (read from the device when omitted)
+        #[arg(long)]
+        count: Option<u8>,
+
+        /// Channel to target (defaults to 0)
+        #[arg(short = 'c', long, default_value_t = 0)]
+        channel: u8,
     },
 
     /// List all connected BlinkStick devices
-    List,
+    List {
+        /// Print the devices as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print a line whenever a BlinkStick is plugged in or removed
+    Watch {
+        /// Poll the bus every N milliseconds instead of using hotplug events
+        #[arg(long)]
+        poll: Option<u64>,
+    },
 
     /// Get info about BlinkStick device
     Info,
@@ -62,6 +223,19 @@ enum Commands {
     /// Turn off BlinkStick (set color to black)
     Off,
 
+    /// Show or change the output mode (normal, inverse, ws2812)
+    Mode {
+        /// Mode to switch to; prints the current mode when omitted
+        mode: Option<Mode>,
+    },
+
+    /// Read or write the device info blocks
+    #[command(arg_required_else_help = true)]
+    InfoBlock {
+        #[command(subcommand)]
+        action: InfoBlockAction,
+    },
+
     /// List all available color names
     ListColors,
 
@@ -73,6 +247,40 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum InfoBlockAction {
+    /// Print the contents of an info block
+    Get {
+        /// Info block number (block 1 holds the device name)
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        block: u8,
+
+        /// Print the raw bytes as hex instead of text
+        #[arg(long)]
+        hex: bool,
+    },
+
+    /// Write text into an info block (up to 32 bytes)
+    Set {
+        /// Info block number (block 1 holds the device name)
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        block: u8,
+
+        /// Text to store
+        data: String,
+    },
+}
+
+fn open_device(builder: &BlinkStickBuilder) -> Result<BlinkStick> {
+    match builder.open() {
+        Ok(blinkstick) => Ok(blinkstick),
+        Err(err) if err.is_permission_denied() => Err(anyhow::Error::new(err).context(
+            "Permission denied. Run 'sudo blinkstick add-udev-rule' to allow access without root",
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
 fn parse_color(color_str: &str) -> Result<RgbColor> {
     // First try named color
     if let Some(color) = RgbColor::from_name(color_str) {
@@ -92,8 +300,23 @@ fn parse_color(color_str: &str) -> Result<RgbColor> {
     anyhow::bail!("Invalid color: {}", color_str)
 }
 
+fn parse_led_range(range: &str) -> Result<Range<u8>, String> {
+    let (first, last) = range
+        .split_once('-')
+        .ok_or_else(|| format!("expected FIRST-LAST, got '{}'", range))?;
+    let first: u8 = first.trim().parse().map_err(|e| format!("{}", e))?;
+    let last: u8 = last.trim().parse().map_err(|e| format!("{}", e))?;
+
+    if first > last {
+        return Err(format!("{} is after {}", first, last));
+    }
+
+    Ok(first..last.saturating_add(1))
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let builder = cli.builder();
 
     match cli.command {
         Commands::SetColor {
@@ -103,7 +326,7 @@ fn main() -> Result<()> {
             all,
             channel,
         } => {
-            let blinkstick = BlinkStick::find_first()?;
+            let blinkstick = open_device(&builder)?;
             let color = parse_color(&color)?;
 
             if all || count.is_some() {
@@ -123,15 +346,11 @@ fn main() -> Result<()> {
             } else {
                 let index = index.unwrap_or(0);
 
-                if index == 0 {
-                    blinkstick.set_color(&color)?;
-                } else {
-                    blinkstick.set_color_indexed(index, &color)?;
-                }
+                blinkstick.set_color_at(channel, index, &color)?;
 
                 println!(
-                    "Set color to RGB({}, {}, {}) at index {}",
-                    color.r, color.g, color.b, index
+                    "Set color to RGB({}, {}, {}) at index {} (channel {})",
+                    color.r, color.g, color.b, index, channel
                 );
             }
         }
@@ -140,8 +359,9 @@ fn main() -> Result<()> {
             color,
             duration,
             steps,
+            easing,
         } => {
-            let blinkstick = BlinkStick::find_first()?;
+            let blinkstick = open_device(&builder)?;
             let color = parse_color(&color)?;
 
             println!(
@@ -149,31 +369,163 @@ fn main() -> Result<()> {
                 color.r, color.g, color.b, duration, steps
             );
 
-            blinkstick.pulse(&color, duration, steps)?;
+            blinkstick.pulse_with(&color, duration, steps, easing)?;
+        }
+        Commands::Morph {
+            color,
+            duration,
+            easing,
+        } => {
+            let blinkstick = open_device(&builder)?;
+            let color = parse_color(&color)?;
+
+            println!(
+                "Morphing to RGB({}, {}, {}) over {}ms",
+                color.r, color.g, color.b, duration
+            );
+
+            blinkstick.morph_with(&color, Duration::from_millis(duration), easing)?;
+        }
+
+        Commands::Blink {
+            color,
+            times,
+            on,
+            off,
+            period,
+            duty,
+            index,
+            range,
+            all,
+            channel,
+            restore,
+        } => {
+            let blinkstick = open_device(&builder)?;
+            let color = parse_color(&color)?;
+
+            let blink = match (period, duty) {
+                (Some(period), Some(duty)) => Blink::duty_cycle(
+                    color.clone(),
+                    Duration::from_millis(period),
+                    duty as f32 / 100.0,
+                ),
+                _ => Blink::new(
+                    color.clone(),
+                    Duration::from_millis(on),
+                    Duration::from_millis(off),
+                ),
+            };
+
+            let target = if all {
+                BlinkTarget::Channel
+            } else if let Some(range) = range {
+                BlinkTarget::Range(range)
+            } else {
+                BlinkTarget::Led(index.unwrap_or(0))
+            };
+
+            println!(
+                "Blinking RGB({}, {}, {}) {} time(s) (channel {})",
+                color.r, color.g, color.b, times, channel
+            );
+
+            blinkstick.blink_with(
+                &mut blink.times(times),
+                &BlinkOptions {
+                    channel,
+                    target,
+                    restore,
+                },
+            )?;
+        }
+
+        Commands::Rainbow {
+            moving,
+            period,
+            saturation,
+            brightness,
+            reverse,
+            duration,
+            count,
+            channel,
+        } => {
+            let blinkstick = open_device(&builder)?;
+            let led_count = match count {
+                Some(count) => count,
+                None => blinkstick
+                    .get_led_count(channel)
+                    .context("Failed to determine LED count automatically. Try passing --count")?,
+            };
+
+            let period = Duration::from_millis(period);
+            let rainbow = if moving {
+                Rainbow::moving(period)
+            } else {
+                Rainbow::cycle(period)
+            };
+            let mut rainbow = rainbow
+                .saturation(saturation as f32 / 100.0)
+                .brightness(brightness as f32 / 100.0)
+                .direction(if reverse {
+                    Direction::Reverse
+                } else {
+                    Direction::Forward
+                });
+
+            let engine = Engine::new(led_count).channel(channel);
+
+            println!(
+                "Running rainbow on {} LEDs (channel {})",
+                led_count, channel
+            );
+
+            match duration {
+                Some(duration) => {
+                    engine.play_for(&blinkstick, &mut rainbow, Duration::from_millis(duration))?;
+                    blinkstick.set_all_colors(channel, led_count, &RgbColor::default())?;
+                }
+                None => engine.play(&blinkstick, &mut rainbow)?,
+            }
         }
 
-        Commands::List => {
+        Commands::List { json } => {
             let devices = blinkstick::find_blinksticks()?;
 
+            if json {
+                println!("{}", serde_json::to_string_pretty(&devices)?);
+                return Ok(());
+            }
+
             if devices.is_empty() {
                 println!("No BlinkStick devices found");
                 return Ok(());
             }
 
             println!("Found {} BlinkStick device(s):", devices.len());
+            print_device_table(&devices);
+        }
 
-            for (i, device) in devices.iter().enumerate() {
-                let blinkstick = BlinkStick::open(device.clone())?;
-                let serial = blinkstick
-                    .get_serial()
-                    .unwrap_or_else(|_| "Unknown".to_string());
-
-                println!("  {}. Serial: {}", i + 1, serial);
+        Commands::Watch { poll } => {
+            let watcher = match poll {
+                Some(interval) => DeviceWatcher::polling(Duration::from_millis(interval)),
+                None => DeviceWatcher::new()?,
+            };
+
+            for event in watcher {
+                match event {
+                    DeviceEvent::Attached(info) => println!(
+                        "attached  {}  {}  {}",
+                        info.serial.as_deref().unwrap_or("Unknown"),
+                        info.variant,
+                        info.path
+                    ),
+                    DeviceEvent::Detached(serial) => println!("detached  {}", serial),
+                }
             }
         }
 
         Commands::Info => {
-            let blinkstick = BlinkStick::find_first()?;
+            let blinkstick = open_device(&builder)?;
             let serial = blinkstick
                 .get_serial()
                 .unwrap_or_else(|_| "Unknown".to_string());
@@ -181,6 +533,12 @@ fn main() -> Result<()> {
 
             println!("BlinkStick Information:");
             println!("  Serial: {}", serial);
+            println!("  Variant: {}", blinkstick.variant());
+            if let Ok(name) = blinkstick.name() {
+                if !name.is_empty() {
+                    println!("  Name: {}", name);
+                }
+            }
             println!(
                 "  Current Color: RGB({}, {}, {})",
                 color.r, color.g, color.b
@@ -188,7 +546,7 @@ fn main() -> Result<()> {
         }
 
         Commands::Off => {
-            let blinkstick = BlinkStick::find_first()?;
+            let blinkstick = open_device(&builder)?;
             let off_color = RgbColor::new(0, 0, 0);
 
             match blinkstick.get_led_count(0) {
@@ -203,6 +561,48 @@ fn main() -> Result<()> {
             }
         }
 
+        Commands::Mode { mode } => {
+            let blinkstick = open_device(&builder)?;
+
+            match mode {
+                Some(mode) => {
+                    blinkstick.set_mode(mode)?;
+                    println!("Mode set to {}", mode);
+                }
+                None => println!("{}", blinkstick.get_mode()?),
+            }
+        }
+
+        Commands::InfoBlock { action } => {
+            let blinkstick = open_device(&builder)?;
+
+            match action {
+                InfoBlockAction::Get { block, hex } => {
+                    let data = match block {
+                        1 => blinkstick.get_info_block1()?,
+                        _ => blinkstick.get_info_block2()?,
+                    };
+
+                    if hex {
+                        let bytes: Vec<String> =
+                            data.iter().map(|b| format!("{:02x}", b)).collect();
+                        println!("{}", bytes.join(" "));
+                    } else {
+                        let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                        println!("{}", String::from_utf8_lossy(&data[..end]));
+                    }
+                }
+                InfoBlockAction::Set { block, data } => {
+                    match block {
+                        1 => blinkstick.set_info_block1(data.as_bytes())?,
+                        _ => blinkstick.set_info_block2(data.as_bytes())?,
+                    }
+
+                    println!("Info block {} set to \"{}\"", block, data);
+                }
+            }
+        }
+
         Commands::ListColors => {
             list_available_colors();
         }
@@ -227,6 +627,60 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn print_device_table(devices: &[DeviceInfo]) {
+    let header = [
+        "#", "Serial", "Variant", "Firmware", "Bus", "Address", "Path", "Product", "Openable",
+    ];
+
+    let rows: Vec<Vec<String>> = devices
+        .iter()
+        .enumerate()
+        .map(|(i, device)| {
+            let unknown = || "Unknown".to_string();
+            vec![
+                (i + 1).to_string(),
+                device.serial.clone().unwrap_or_else(unknown),
+                device.variant.to_string(),
+                device.firmware_version.clone(),
+                device.bus.to_string(),
+                device.address.to_string(),
+                device.path.to_string(),
+                device.product.clone().unwrap_or_else(unknown),
+                if device.openable { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(col, title)| {
+            rows.iter()
+                .map(|row| row[col].len())
+                .chain(std::iter::once(title.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(header.to_vec()));
+
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
 fn list_available_colors() {
     let colors = [
         "aliceblue",
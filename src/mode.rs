@@ -0,0 +1,62 @@
+//! Output modes understood by the BlinkStick firmware.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// How the firmware drives the LED outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Plain RGB outputs (common cathode)
+    Normal,
+    /// Inverted RGB outputs for common anode LEDs
+    Inverse,
+    /// WS2812 smart pixel data on every channel
+    Ws2812,
+}
+
+impl Mode {
+    /// Decode the byte stored in the mode report
+    pub fn from_byte(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Normal),
+            1 => Some(Self::Inverse),
+            2 => Some(Self::Ws2812),
+            _ => None,
+        }
+    }
+
+    /// The byte sent in the mode report
+    pub fn as_byte(self) -> u8 {
+        match self {
+            Self::Normal => 0,
+            Self::Inverse => 1,
+            Self::Ws2812 => 2,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "normal",
+            Self::Inverse => "inverse",
+            Self::Ws2812 => "ws2812",
+        })
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "normal" | "0" => Ok(Self::Normal),
+            "inverse" | "1" => Ok(Self::Inverse),
+            "ws2812" | "2" => Ok(Self::Ws2812),
+            _ => Err(format!(
+                "Invalid mode: {} (expected normal, inverse or ws2812)",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! A BlinkStick handle that survives unplugging and USB resets.
+
+use crate::{BlinkStick, BlinkStickError, Result, RgbColor, Transport, UsbTransport};
+use std::thread;
+use std::time::Duration;
+
+/// How `ResilientBlinkStick` waits between reconnect attempts
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_delay: Duration,
+    /// Factor applied to the delay after every failed attempt
+    pub multiplier: f64,
+    /// Give up after this many attempts; `None` retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            multiplier: 2.0,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before the given attempt (starting at 0)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt as i32);
+        self.initial_delay.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+type Connector<T> = Box<dyn FnMut() -> Result<BlinkStick<T>> + Send>;
+
+/// BlinkStick wrapper that reopens the device when it disappears
+///
+/// After reconnecting, the last frame written to each channel is replayed so
+/// the LEDs show what they showed before the device went away.
+pub struct ResilientBlinkStick<T: Transport = UsbTransport> {
+    connect: Connector<T>,
+    device: Option<BlinkStick<T>>,
+    frames: Vec<Vec<RgbColor>>,
+    policy: ReconnectPolicy,
+}
+
+impl ResilientBlinkStick<UsbTransport> {
+    /// Open the device with the given serial number and keep tracking it
+    pub fn open_by_serial(serial: &str) -> Result<Self> {
+        let serial = serial.to_string();
+        Self::with_connector(move || BlinkStick::open_by_serial(&serial))
+    }
+
+    /// Open the first device and keep tracking it by its serial number
+    pub fn find_first() -> Result<Self> {
+        let device = BlinkStick::find_first()?;
+        let serial = device.get_serial()?;
+
+        Ok(Self::from_device(device, move || {
+            BlinkStick::open_by_serial(&serial)
+        }))
+    }
+}
+
+impl<T: Transport> ResilientBlinkStick<T> {
+    /// Track a device opened by `connect`, which is called again after failures
+    pub fn with_connector<F>(mut connect: F) -> Result<Self>
+    where
+        F: FnMut() -> Result<BlinkStick<T>> + Send + 'static,
+    {
+        let device = connect()?;
+
+        Ok(Self::from_device(device, connect))
+    }
+
+    /// Track an already opened device; `connect` is only called after failures
+    pub fn from_device<F>(device: BlinkStick<T>, connect: F) -> Self
+    where
+        F: FnMut() -> Result<BlinkStick<T>> + Send + 'static,
+    {
+        Self {
+            connect: Box::new(connect),
+            device: Some(device),
+            frames: Vec::new(),
+            policy: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Set the reconnect backoff
+    pub fn with_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Whether a device handle is currently open
+    pub fn is_connected(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Run an operation, reconnecting and retrying once if the device is gone
+    ///
+    /// Only disconnects trigger a reconnect. Other failures, including
+    /// timeouts and stalls on a device that is still attached, are returned
+    /// as they are.
+    pub fn run<R, F>(&mut self, op: F) -> Result<R>
+    where
+        F: Fn(&BlinkStick<T>) -> Result<R>,
+    {
+        if let Some(device) = &self.device {
+            match op(device) {
+                Ok(result) => return Ok(result),
+                Err(err) if !err.is_disconnected() => return Err(err),
+                Err(_) => self.disconnect(),
+            }
+        }
+
+        self.reconnect()?;
+
+        let device = self.device.as_ref().ok_or(BlinkStickError::NoDeviceFound)?;
+
+        op(device)
+    }
+
+    /// Drop the current handle and open the device again, replaying the last frames
+    ///
+    /// Once the policy gives up, the error of the last attempt is returned.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.disconnect();
+
+        let mut attempt = 0;
+
+        loop {
+            thread::sleep(self.policy.delay(attempt));
+
+            let err = match (self.connect)() {
+                Ok(device) => match Self::replay(&device, &self.frames) {
+                    Ok(()) => {
+                        self.device = Some(device);
+                        return Ok(());
+                    }
+                    Err(err) => err,
+                },
+                Err(err) => err,
+            };
+
+            attempt += 1;
+
+            if self.policy.max_attempts.is_some_and(|max| attempt >= max) {
+                return Err(err);
+            }
+        }
+    }
+
+    /// Set the color of the first LED
+    pub fn set_color(&mut self, color: &RgbColor) -> Result<()> {
+        self.run(|device| device.set_color(color))
+    }
+
+    /// Set the color of a single LED on a channel
+    pub fn set_color_at(&mut self, channel: u8, index: u8, color: &RgbColor) -> Result<()> {
+        self.run(|device| device.set_color_at(channel, index, color))
+    }
+
+    /// Set colors for multiple LEDs at once
+    pub fn set_colors(&mut self, channel: u8, leds: &[RgbColor]) -> Result<()> {
+        self.run(|device| device.set_colors(channel, leds))
+    }
+
+    /// Fill all LEDs on a channel with the same color
+    pub fn set_all_colors(&mut self, channel: u8, led_count: u8, color: &RgbColor) -> Result<()> {
+        self.run(|device| device.set_all_colors(channel, led_count, color))
+    }
+
+    /// Get the current color of the first LED
+    pub fn get_color(&mut self) -> Result<RgbColor> {
+        self.run(|device| device.get_color())
+    }
+
+    /// Attempt to read the number of LEDs available on a given channel
+    pub fn get_led_count(&mut self, channel: u8) -> Result<u8> {
+        self.run(|device| device.get_led_count(channel))
+    }
+
+    /// Get the device serial number
+    pub fn get_serial(&mut self) -> Result<String> {
+        self.run(|device| device.get_serial())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(device) = self.device.take() {
+            self.frames = device.last_frames();
+        }
+    }
+
+    fn replay(device: &BlinkStick<T>, frames: &[Vec<RgbColor>]) -> Result<()> {
+        for (channel, frame) in frames.iter().enumerate() {
+            device.set_colors(channel as u8, frame)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+//! Choosing one BlinkStick among several connected devices.
+
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Physical location of a USB device: bus number and chain of hub ports
+///
+/// Written the way Linux names USB devices in sysfs, e.g. `1-2.3` for bus 1,
+/// port 3 of the hub on port 2.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DevicePath {
+    pub bus: u8,
+    pub ports: Vec<u8>,
+}
+
+impl DevicePath {
+    pub fn new(bus: u8, ports: Vec<u8>) -> Self {
+        Self { bus, ports }
+    }
+}
+
+impl fmt::Display for DevicePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ports: Vec<String> = self.ports.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}-{}", self.bus, ports.join("."))
+    }
+}
+
+impl Serialize for DevicePath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for DevicePath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid device path: {} (expected bus-port[.port...])", s);
+
+        let (bus, ports) = s.split_once('-').ok_or_else(invalid)?;
+        let bus = bus.parse().map_err(|_| invalid())?;
+        let ports = ports
+            .split('.')
+            .map(|port| port.parse().map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        Ok(Self { bus, ports })
+    }
+}
+
+/// Criteria for picking a device out of an enumeration
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DeviceSelector {
+    /// Whichever device is enumerated first
+    #[default]
+    First,
+    /// The device with this serial number
+    Serial(String),
+    /// The device whose info block 1 holds this name
+    Name(String),
+    /// The device plugged into this bus and port chain
+    Path(DevicePath),
+}
+
+impl DeviceSelector {
+    /// Whether a candidate satisfies the selector
+    pub fn matches<C: DeviceCandidate>(&self, candidate: &C) -> bool {
+        match self {
+            Self::First => true,
+            Self::Serial(serial) => candidate.serial().as_deref() == Some(serial.as_str()),
+            Self::Name(name) => candidate.name().as_deref() == Some(name.as_str()),
+            Self::Path(path) => candidate.path().as_ref() == Some(path),
+        }
+    }
+
+    /// Pick the first matching candidate
+    pub fn select<C, I>(&self, candidates: I) -> Option<C>
+    where
+        C: DeviceCandidate,
+        I: IntoIterator<Item = C>,
+    {
+        candidates.into_iter().find(|c| self.matches(c))
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "any device"),
+            Self::Serial(serial) => write!(f, "serial {}", serial),
+            Self::Name(name) => write!(f, "name {}", name),
+            Self::Path(path) => write!(f, "path {}", path),
+        }
+    }
+}
+
+/// An enumerated device that can be matched against a `DeviceSelector`
+///
+/// Properties are looked up lazily, so reading the name (which needs the
+/// device to be opened) only happens when selecting by name.
+pub trait DeviceCandidate {
+    fn serial(&self) -> Option<String>;
+    fn name(&self) -> Option<String>;
+    fn path(&self) -> Option<DevicePath>;
+}
@@ -0,0 +1,143 @@
+//! A BlinkStick handle that can be used from several threads at once.
+
+use crate::{BlinkStick, BlinkStickVariant, Result, RgbColor, Transport, UsbTransport};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+struct Inner<T: Transport> {
+    device: Mutex<BlinkStick<T>>,
+    // Newest frame per channel that no writer has sent yet
+    pending: Mutex<HashMap<u8, Pending>>,
+}
+
+// Frames coalesced into one transfer share its outcome
+struct Pending {
+    frame: Vec<RgbColor>,
+    outcome: Arc<OnceLock<Result<()>>>,
+}
+
+/// Cloneable, thread-safe BlinkStick handle
+///
+/// Transfers from all clones are serialized. Whole-frame writes are coalesced:
+/// while one frame is on the wire, later frames for the same channel replace
+/// each other and only the newest is sent. A writer whose frame was superseded
+/// returns the result of the transfer that carried the newer frame. Single-LED
+/// writes are never dropped.
+pub struct SharedBlinkStick<T: Transport = UsbTransport> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T: Transport> Clone for SharedBlinkStick<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Transport> From<BlinkStick<T>> for SharedBlinkStick<T> {
+    fn from(device: BlinkStick<T>) -> Self {
+        Self::new(device)
+    }
+}
+
+impl<T: Transport> SharedBlinkStick<T> {
+    /// Share an opened device
+    pub fn new(device: BlinkStick<T>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                device: Mutex::new(device),
+                pending: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Run `op` with exclusive access to the device
+    pub fn with_device<R>(&self, op: impl FnOnce(&BlinkStick<T>) -> R) -> R {
+        op(&self.device())
+    }
+
+    /// The hardware variant of the device
+    pub fn variant(&self) -> BlinkStickVariant {
+        self.device().variant()
+    }
+
+    /// Set colors for multiple LEDs at once, skipping the transfer if a newer
+    /// frame for the channel arrives first
+    ///
+    /// A skipped writer gets the result of the transfer that replaced its frame.
+    pub fn set_colors(&self, channel: u8, leds: &[RgbColor]) -> Result<()> {
+        let outcome = {
+            let mut pending = self.pending();
+            let entry = pending.entry(channel).or_insert_with(|| Pending {
+                frame: Vec::new(),
+                outcome: Arc::new(OnceLock::new()),
+            });
+            entry.frame = leds.to_vec();
+            entry.outcome.clone()
+        };
+
+        let device = self.device();
+
+        // Whoever got the lock first already sent our frame or a newer one and
+        // stored the result before releasing the device
+        if let Some(pending) = self.pending().remove(&channel) {
+            let result = device.set_colors(channel, &pending.frame);
+            let _ = pending.outcome.set(result);
+        }
+
+        match outcome.get() {
+            Some(Ok(())) => Ok(()),
+            Some(Err(err)) => Err(err.duplicate()),
+            // The sending writer panicked mid-transfer
+            None => device.set_colors(channel, leds),
+        }
+    }
+
+    /// Fill all LEDs on a channel with the same color
+    pub fn set_all_colors(&self, channel: u8, led_count: u8, color: &RgbColor) -> Result<()> {
+        if led_count == 0 {
+            return Ok(());
+        }
+
+        self.set_colors(channel, &vec![color.clone(); led_count as usize])
+    }
+
+    /// Set the color of the first LED
+    pub fn set_color(&self, color: &RgbColor) -> Result<()> {
+        self.device().set_color(color)
+    }
+
+    /// Set the color of a single LED on a channel
+    pub fn set_color_at(&self, channel: u8, index: u8, color: &RgbColor) -> Result<()> {
+        self.device().set_color_at(channel, index, color)
+    }
+
+    /// Get the current color of the first LED
+    pub fn get_color(&self) -> Result<RgbColor> {
+        self.device().get_color()
+    }
+
+    /// Attempt to read the number of LEDs available on a given channel
+    pub fn get_led_count(&self, channel: u8) -> Result<u8> {
+        self.device().get_led_count(channel)
+    }
+
+    /// Get the device serial number
+    pub fn get_serial(&self) -> Result<String> {
+        self.device().get_serial()
+    }
+
+    /// The colors last written to a channel, if anything was written
+    pub fn last_frame(&self, channel: u8) -> Option<Vec<RgbColor>> {
+        self.device().last_frame(channel)
+    }
+
+    fn device(&self) -> MutexGuard<'_, BlinkStick<T>> {
+        self.inner.device.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn pending(&self) -> MutexGuard<'_, HashMap<u8, Pending>> {
+        self.inner.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
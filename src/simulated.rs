@@ -0,0 +1,417 @@
+//! In-memory BlinkStick that emulates the device firmware.
+//!
+//! `SimulatedBlinkStick` implements [`Transport`] so a `BlinkStick` can be
+//! driven without hardware. It decodes the same reports the firmware does and
+//! keeps per-channel LED state that tests can inspect.
+
+use crate::transport::{Transport, SERIAL_DESCRIPTOR_INDEX};
+use crate::{BlinkStickError, BlinkStickVariant, Operation, Result, RgbColor};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// A report written to the simulated device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub w_value: u16,
+    pub w_index: u16,
+    pub data: Vec<u8>,
+}
+
+impl Report {
+    /// The report ID addressed by this transfer
+    pub fn report_id(&self) -> u8 {
+        (self.w_value & 0xFF) as u8
+    }
+}
+
+struct State {
+    serial: String,
+    manufacturer: String,
+    product: String,
+    version: u16,
+    channels: Vec<Vec<RgbColor>>,
+    info_blocks: [[u8; 32]; 2],
+    mode: u8,
+    reports: Vec<Report>,
+    transfers: usize,
+    fail_next: usize,
+    fail_with: rusb::Error,
+    connected: bool,
+    timeout: Option<Duration>,
+}
+
+/// Simulated BlinkStick firmware
+///
+/// Clones share the same device state, so a clone kept by a test observes
+/// everything written through a `BlinkStick` that owns the other.
+#[derive(Clone)]
+pub struct SimulatedBlinkStick {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for SimulatedBlinkStick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedBlinkStick {
+    /// Create a device with a single channel of 8 LEDs
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                serial: "BS000001-3.0".to_string(),
+                manufacturer: "Agile Innovative Ltd".to_string(),
+                product: "BlinkStick".to_string(),
+                version: 0x0200,
+                channels: vec![vec![RgbColor::default(); 8]],
+                info_blocks: [[0; 32]; 2],
+                mode: 0,
+                reports: Vec::new(),
+                transfers: 0,
+                fail_next: 0,
+                fail_with: rusb::Error::Pipe,
+                connected: true,
+                timeout: None,
+            })),
+        }
+    }
+
+    /// Set the serial number reported through the string descriptor
+    pub fn with_serial(self, serial: &str) -> Self {
+        self.state().serial = serial.to_string();
+        self
+    }
+
+    /// Set the bcdDevice release number of the device descriptor
+    pub fn with_version(self, version: u16) -> Self {
+        self.state().version = version;
+        self
+    }
+
+    /// Emulate a specific model: serial suffix, release number and LED layout
+    pub fn with_variant(self, variant: BlinkStickVariant) -> Self {
+        let (generation, version) = match variant {
+            BlinkStickVariant::BlinkStick => (1, 0x0100),
+            BlinkStickVariant::Pro => (2, 0x0100),
+            BlinkStickVariant::Square => (3, 0x0200),
+            BlinkStickVariant::Strip => (3, 0x0201),
+            BlinkStickVariant::Nano => (3, 0x0202),
+            BlinkStickVariant::Flex => (3, 0x0203),
+            BlinkStickVariant::Unknown => (0, 0x0000),
+        };
+        let leds = vec![variant.default_led_count(); variant.channel_count() as usize];
+
+        self.with_serial(&format!("BS000001-{}.0", generation))
+            .with_version(version)
+            .with_channels(&leds)
+    }
+
+    /// Store a device name in the first info block
+    pub fn with_name(self, name: &str) -> Self {
+        let mut block = [0u8; 32];
+        let len = name.len().min(block.len());
+        block[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+        self.state().info_blocks[0] = block;
+        self
+    }
+
+    /// Configure the number of LEDs on each channel
+    pub fn with_channels(self, led_counts: &[u8]) -> Self {
+        self.state().channels = led_counts
+            .iter()
+            .map(|&count| vec![RgbColor::default(); count as usize])
+            .collect();
+        self
+    }
+
+    /// Make the next `count` transfers fail with a stall
+    pub fn fail_next(&self, count: usize) {
+        self.fail_next_with(count, rusb::Error::Pipe);
+    }
+
+    /// Make the next `count` transfers fail with the given libusb error
+    pub fn fail_next_with(&self, count: usize, error: rusb::Error) {
+        let mut state = self.state();
+        state.fail_next = count;
+        state.fail_with = error;
+    }
+
+    /// Simulate unplugging (`false`) or replugging (`true`) the device
+    ///
+    /// Unplugging cuts power, so every LED is off once the device is back.
+    pub fn set_connected(&self, connected: bool) {
+        let mut state = self.state();
+        state.connected = connected;
+
+        if !connected {
+            for channel in &mut state.channels {
+                channel.fill(RgbColor::default());
+            }
+        }
+    }
+
+    /// Whether the device is currently plugged in
+    pub fn is_connected(&self) -> bool {
+        self.state().connected
+    }
+
+    /// Current colors of every LED on a channel
+    pub fn leds(&self, channel: u8) -> Vec<RgbColor> {
+        self.state()
+            .channels
+            .get(channel as usize)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Current color of a single LED
+    pub fn led(&self, channel: u8, index: u8) -> Option<RgbColor> {
+        self.state()
+            .channels
+            .get(channel as usize)?
+            .get(index as usize)
+            .cloned()
+    }
+
+    /// Contents of info block 1 or 2, or `None` for any other block number
+    pub fn info_block(&self, block: usize) -> Option<[u8; 32]> {
+        let index = block.checked_sub(1)?;
+        self.state().info_blocks.get(index).copied()
+    }
+
+    /// The raw output mode byte
+    pub fn mode(&self) -> u8 {
+        self.state().mode
+    }
+
+    /// Every report written to the device so far
+    pub fn reports(&self) -> Vec<Report> {
+        self.state().reports.clone()
+    }
+
+    /// Forget the recorded reports
+    pub fn clear_reports(&self) {
+        self.state().reports.clear();
+    }
+
+    /// The transfer timeout set by the driver, if any
+    pub fn timeout(&self) -> Option<Duration> {
+        self.state().timeout
+    }
+
+    /// Number of transfers attempted against the device, including failed ones
+    pub fn transfer_count(&self) -> usize {
+        self.state().transfers
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Account for a transfer and decide whether it should fail
+    fn begin_transfer(state: &mut State) -> rusb::Result<()> {
+        state.transfers += 1;
+
+        if !state.connected {
+            return Err(rusb::Error::NoDevice);
+        }
+
+        if state.fail_next > 0 {
+            state.fail_next -= 1;
+            return Err(state.fail_with);
+        }
+
+        Ok(())
+    }
+
+    // Malformed or unknown reports stall the endpoint like the firmware does
+    fn set_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> rusb::Result<()> {
+        let mut state = self.state();
+        Self::begin_transfer(&mut state)?;
+
+        let report_id = (w_value & 0xFF) as u8;
+
+        match report_id {
+            1 => {
+                if data.len() < 4 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                if let Some(led) = state.channels.get_mut(0).and_then(|c| c.first_mut()) {
+                    *led = RgbColor::new(data[1], data[2], data[3]);
+                }
+            }
+            2 | 3 => {
+                if data.len() < 33 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                state.info_blocks[report_id as usize - 2].copy_from_slice(&data[1..33]);
+            }
+            4 => {
+                if data.len() < 2 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                state.mode = data[1];
+            }
+            5 => {
+                if data.len() < 6 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                let channel = state
+                    .channels
+                    .get_mut(data[1] as usize)
+                    .ok_or(rusb::Error::Pipe)?;
+
+                // The firmware ignores indices past the end of the strip
+                if let Some(led) = channel.get_mut(data[2] as usize) {
+                    *led = RgbColor::new(data[3], data[4], data[5]);
+                }
+            }
+            6..=9 => {
+                if data.len() < 2 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                let channel = state
+                    .channels
+                    .get_mut(data[1] as usize)
+                    .ok_or(rusb::Error::Pipe)?;
+
+                for (led, grb) in channel.iter_mut().zip(data[2..].chunks_exact(3)) {
+                    *led = RgbColor::new(grb[1], grb[0], grb[2]);
+                }
+            }
+            0x81 => {
+                if data.len() < 2 {
+                    return Err(rusb::Error::Pipe);
+                }
+
+                if let Some(channel) = state.channels.get_mut(0) {
+                    channel.resize(data[1] as usize, RgbColor::default());
+                }
+            }
+            _ => return Err(rusb::Error::Pipe),
+        }
+
+        state.reports.push(Report {
+            w_value,
+            w_index,
+            data: data.to_vec(),
+        });
+
+        Ok(())
+    }
+
+    fn get_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> rusb::Result<usize> {
+        let mut state = self.state();
+        Self::begin_transfer(&mut state)?;
+
+        let report_id = (w_value & 0xFF) as u8;
+
+        let response = match report_id {
+            1 => {
+                let led = state
+                    .channels
+                    .first()
+                    .and_then(|c| c.first())
+                    .cloned()
+                    .unwrap_or_default();
+                vec![1, led.r, led.g, led.b]
+            }
+            2 | 3 => {
+                let mut response = vec![report_id];
+                response.extend_from_slice(&state.info_blocks[report_id as usize - 2]);
+                response
+            }
+            4 => vec![4, state.mode],
+            6..=9 => {
+                let channel = state
+                    .channels
+                    .get(w_index as usize)
+                    .ok_or(rusb::Error::Pipe)?;
+                let capacity = 8usize << (report_id - 6);
+                let mut response = vec![report_id, w_index as u8];
+
+                for led in channel.iter().take(capacity) {
+                    response.extend_from_slice(&[led.g, led.r, led.b]);
+                }
+                response.resize(2 + capacity * 3, 0);
+                response
+            }
+            0x81 => {
+                let channel = state
+                    .channels
+                    .get(w_index as usize)
+                    .ok_or(rusb::Error::Pipe)?;
+                vec![0x81, channel.len() as u8]
+            }
+            _ => return Err(rusb::Error::Pipe),
+        };
+
+        let len = response.len().min(data.len());
+        data[..len].copy_from_slice(&response[..len]);
+
+        Ok(len)
+    }
+}
+
+impl Transport for SimulatedBlinkStick {
+    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+        self.set_report(0x0300 | data[0] as u16, 0, data)
+            .map_err(BlinkStickError::usb(Operation::SendReport, Some(data[0])))
+    }
+
+    fn send_class_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> Result<()> {
+        self.set_report(w_value, w_index, data)
+            .map_err(BlinkStickError::usb(
+                Operation::SendReport,
+                Some((w_value & 0xFF) as u8),
+            ))
+    }
+
+    fn get_feature_report(&self, data: &mut [u8]) -> Result<()> {
+        let report_id = data[0];
+
+        self.get_report(0x0300 | report_id as u16, 0, data)
+            .map_err(BlinkStickError::usb(Operation::ReadReport, Some(report_id)))?;
+        Ok(())
+    }
+
+    fn read_class_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> Result<usize> {
+        self.get_report(w_value, w_index, data)
+            .map_err(BlinkStickError::usb(
+                Operation::ReadReport,
+                Some((w_value & 0xFF) as u8),
+            ))
+    }
+
+    fn read_string_descriptor(&self, index: u8) -> Result<String> {
+        let operation = Operation::ReadString(index);
+        let mut state = self.state();
+        Self::begin_transfer(&mut state).map_err(BlinkStickError::usb(operation, None))?;
+
+        match index {
+            1 => Ok(state.manufacturer.clone()),
+            2 => Ok(state.product.clone()),
+            SERIAL_DESCRIPTOR_INDEX => Ok(state.serial.clone()),
+            _ => Err(BlinkStickError::Usb {
+                operation,
+                report_id: None,
+                source: rusb::Error::Pipe,
+            }),
+        }
+    }
+
+    fn device_version(&self) -> Result<u16> {
+        Ok(self.state().version)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.state().timeout = Some(timeout);
+    }
+}
@@ -0,0 +1,231 @@
+//! Keyframe animations sampled at arbitrary times.
+//!
+//! A [`Timeline`] is a list of keyframes played one after another. Each
+//! keyframe either shows its colors straight away or fades to them from the
+//! previous keyframe. Frames are a pure function of the sample time, so the
+//! same timeline renders the same frames however it is clocked.
+
+use crate::{Easing, Effect, RgbColor};
+use std::time::Duration;
+
+/// The colors a keyframe ends on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Colors {
+    /// One color on every LED of the channel
+    Fill(RgbColor),
+    /// One color per LED; LEDs past the end of the list are off
+    PerLed(Vec<RgbColor>),
+}
+
+impl Colors {
+    fn at(&self, index: usize) -> RgbColor {
+        match self {
+            Colors::Fill(color) => color.clone(),
+            Colors::PerLed(leds) => leds.get(index).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<RgbColor> for Colors {
+    fn from(color: RgbColor) -> Self {
+        Colors::Fill(color)
+    }
+}
+
+impl From<Vec<RgbColor>> for Colors {
+    fn from(leds: Vec<RgbColor>) -> Self {
+        Colors::PerLed(leds)
+    }
+}
+
+/// One step of a timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    colors: Colors,
+    duration: Duration,
+    // None jumps to the colors, Some fades to them along the curve
+    fade: Option<Easing>,
+}
+
+impl Keyframe {
+    /// Show `colors` for `duration`
+    pub fn hold(colors: impl Into<Colors>, duration: Duration) -> Self {
+        Self {
+            colors: colors.into(),
+            duration,
+            fade: None,
+        }
+    }
+
+    /// Fade from the previous keyframe to `colors` over `duration`
+    pub fn fade(colors: impl Into<Colors>, duration: Duration, easing: Easing) -> Self {
+        Self {
+            colors: colors.into(),
+            duration,
+            fade: Some(easing),
+        }
+    }
+
+    /// How long the keyframe lasts
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// What a timeline does after its last keyframe
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Repeat {
+    /// Play once and hold the last keyframe
+    #[default]
+    Once,
+    /// Start over from the first keyframe
+    Loop,
+    /// Play backwards to the start, then forwards again
+    PingPong,
+}
+
+/// A sequence of keyframes
+///
+/// ```
+/// # use blinkstick::{Easing, RgbColor, Timeline};
+/// # use std::time::Duration;
+/// let alert = Timeline::new()
+///     .hold(RgbColor::new(255, 0, 0), Duration::from_secs(2))
+///     .fade(RgbColor::new(255, 191, 0), Duration::from_millis(500), Easing::Sine)
+///     .blink(RgbColor::new(0, 0, 255), 2, Duration::from_millis(200), Duration::from_millis(200));
+///
+/// assert_eq!(alert.total_duration(), Duration::from_millis(3300));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    keyframes: Vec<Keyframe>,
+    repeat: Repeat,
+}
+
+impl Timeline {
+    /// An empty timeline played once
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a keyframe
+    pub fn keyframe(mut self, keyframe: Keyframe) -> Self {
+        self.keyframes.push(keyframe);
+        self
+    }
+
+    /// Show `colors` for `duration`
+    pub fn hold(self, colors: impl Into<Colors>, duration: Duration) -> Self {
+        self.keyframe(Keyframe::hold(colors, duration))
+    }
+
+    /// Fade to `colors` over `duration`
+    ///
+    /// The first keyframe of a timeline fades in from off.
+    pub fn fade(self, colors: impl Into<Colors>, duration: Duration, easing: Easing) -> Self {
+        self.keyframe(Keyframe::fade(colors, duration, easing))
+    }
+
+    /// Blink `color` on every LED `times` times, ending off
+    pub fn blink(mut self, color: RgbColor, times: u32, on: Duration, off: Duration) -> Self {
+        for _ in 0..times {
+            self = self.hold(color.clone(), on).hold(RgbColor::default(), off);
+        }
+        self
+    }
+
+    /// What happens after the last keyframe
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// The keyframes in playing order
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// Length of one pass through all keyframes
+    pub fn total_duration(&self) -> Duration {
+        self.keyframes
+            .iter()
+            .map(|keyframe| keyframe.duration)
+            .sum()
+    }
+
+    /// The frame shown `at` the given time for a strip of `led_count` LEDs
+    pub fn sample(&self, at: Duration, led_count: usize) -> Vec<RgbColor> {
+        let mut leds = vec![RgbColor::default(); led_count];
+        self.paint(at, &mut leds);
+        leds
+    }
+
+    /// Map a time since the start onto a time within one pass
+    fn local_time(&self, at: Duration) -> Duration {
+        let total = self.total_duration();
+
+        if total.is_zero() {
+            return Duration::ZERO;
+        }
+
+        match self.repeat {
+            Repeat::Once => at.min(total),
+            Repeat::Loop => Duration::from_nanos((at.as_nanos() % total.as_nanos()) as u64),
+            Repeat::PingPong => {
+                let bounced = at.as_nanos() % (2 * total.as_nanos());
+                let reflected = bounced.min(2 * total.as_nanos() - bounced);
+                Duration::from_nanos(reflected as u64)
+            }
+        }
+    }
+
+    fn paint(&self, at: Duration, leds: &mut [RgbColor]) {
+        let Some(last) = self.keyframes.last() else {
+            leds.fill(RgbColor::default());
+            return;
+        };
+
+        let mut time = self.local_time(at);
+        let mut previous: Option<&Colors> = None;
+
+        for keyframe in &self.keyframes {
+            if time < keyframe.duration {
+                let progress = (time.as_secs_f64() / keyframe.duration.as_secs_f64()) as f32;
+
+                for (index, led) in leds.iter_mut().enumerate() {
+                    let target = keyframe.colors.at(index);
+
+                    *led = match keyframe.fade {
+                        Some(easing) => previous
+                            .map(|colors| colors.at(index))
+                            .unwrap_or_default()
+                            .lerp(&target, easing.apply(progress)),
+                        None => target,
+                    };
+                }
+                return;
+            }
+
+            time -= keyframe.duration;
+            previous = Some(&keyframe.colors);
+        }
+
+        // Past the end: every keyframe is complete
+        for (index, led) in leds.iter_mut().enumerate() {
+            *led = last.colors.at(index);
+        }
+    }
+}
+
+impl Effect for Timeline {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        self.paint(elapsed, leds);
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        match self.repeat {
+            Repeat::Once => Some(self.total_duration()),
+            Repeat::Loop | Repeat::PingPong => None,
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Transports carry HID reports between a `BlinkStick` and the hardware.
+//!
+//! The default transport talks to the device through libusb control transfers.
+//! Alternative backends only need to implement the [`Transport`] trait.
+
+use crate::builder::DEFAULT_TIMEOUT;
+use crate::{BlinkStickError, Operation, Result};
+use rusb::{Context, DeviceHandle};
+use std::time::Duration;
+
+/// String descriptor index holding the device serial number
+pub const SERIAL_DESCRIPTOR_INDEX: u8 = 3;
+
+/// Low level report primitives used by `BlinkStick`
+pub trait Transport {
+    /// Send a HID feature report. The first byte of `data` is the report ID.
+    fn send_feature_report(&self, data: &[u8]) -> Result<()>;
+
+    /// Send a class SET_REPORT request with explicit `wValue` and `wIndex`
+    fn send_class_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> Result<()>;
+
+    /// Read a HID feature report. The first byte of `data` selects the report ID.
+    fn get_feature_report(&self, data: &mut [u8]) -> Result<()>;
+
+    /// Issue a class GET_REPORT request and return the number of bytes read
+    fn read_class_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> Result<usize>;
+
+    /// Read a USB string descriptor (e.g. the serial number at index 3)
+    fn read_string_descriptor(&self, index: u8) -> Result<String>;
+
+    /// The bcdDevice release number from the device descriptor
+    fn device_version(&self) -> Result<u16>;
+
+    /// Limit how long a single transfer may take
+    ///
+    /// Backends without a notion of timeouts ignore this.
+    fn set_timeout(&mut self, _timeout: Duration) {}
+}
+
+/// Encode a release number decoded by rusb back into its bcdDevice form
+pub(crate) fn version_to_bcd(version: rusb::Version) -> u16 {
+    let major = version.major() as u16;
+
+    (major / 10) << 12
+        | (major % 10) << 8
+        | (version.minor() as u16) << 4
+        | version.sub_minor() as u16
+}
+
+/// Transport backed by a claimed libusb device handle
+pub struct UsbTransport {
+    handle: DeviceHandle<Context>,
+    timeout: Duration,
+    // Whether the kernel driver was detached from interface 0 when opening
+    reattach_kernel_driver: bool,
+}
+
+impl UsbTransport {
+    /// Wrap an already opened and claimed device handle
+    pub fn new(handle: DeviceHandle<Context>) -> Self {
+        Self {
+            handle,
+            timeout: DEFAULT_TIMEOUT,
+            reattach_kernel_driver: false,
+        }
+    }
+
+    /// Record that the kernel driver was detached from interface 0
+    ///
+    /// The driver is then reattached when the transport is dropped, so other
+    /// HID software can use the device again without replugging it.
+    pub fn with_detached_kernel_driver(mut self, detached: bool) -> Self {
+        self.reattach_kernel_driver = detached;
+        self
+    }
+
+    /// Access the underlying libusb handle
+    pub fn handle(&self) -> &DeviceHandle<Context> {
+        &self.handle
+    }
+
+    /// Timeout applied to every control transfer
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl Transport for UsbTransport {
+    fn send_feature_report(&self, data: &[u8]) -> Result<()> {
+        self.handle
+            .write_control(
+                rusb::request_type(
+                    rusb::Direction::Out,
+                    rusb::RequestType::Class,
+                    rusb::Recipient::Interface,
+                ),
+                0x09,                    // SET_REPORT
+                0x0300 | data[0] as u16, // HID_REPORT_TYPE_FEATURE | report_id
+                0,
+                data,
+                self.timeout,
+            )
+            .map_err(BlinkStickError::usb(Operation::SendReport, Some(data[0])))?;
+
+        Ok(())
+    }
+
+    fn send_class_report(&self, w_value: u16, w_index: u16, data: &[u8]) -> Result<()> {
+        self.handle
+            .write_control(
+                rusb::request_type(
+                    rusb::Direction::Out,
+                    rusb::RequestType::Class,
+                    rusb::Recipient::Interface,
+                ),
+                0x09,
+                w_value,
+                w_index,
+                data,
+                self.timeout,
+            )
+            .map_err(BlinkStickError::usb(
+                Operation::SendReport,
+                Some((w_value & 0xFF) as u8),
+            ))?;
+
+        Ok(())
+    }
+
+    fn get_feature_report(&self, data: &mut [u8]) -> Result<()> {
+        let report_id = data[0];
+
+        self.handle
+            .read_control(
+                rusb::request_type(
+                    rusb::Direction::In,
+                    rusb::RequestType::Class,
+                    rusb::Recipient::Interface,
+                ),
+                0x01,                      // GET_REPORT
+                0x0300 | report_id as u16, // HID_REPORT_TYPE_FEATURE | report_id
+                0,
+                data,
+                self.timeout,
+            )
+            .map_err(BlinkStickError::usb(Operation::ReadReport, Some(report_id)))?;
+
+        Ok(())
+    }
+
+    fn read_class_report(&self, w_value: u16, w_index: u16, data: &mut [u8]) -> Result<usize> {
+        self.handle
+            .read_control(
+                rusb::request_type(
+                    rusb::Direction::In,
+                    rusb::RequestType::Class,
+                    rusb::Recipient::Interface,
+                ),
+                0x01,
+                w_value,
+                w_index,
+                data,
+                self.timeout,
+            )
+            .map_err(BlinkStickError::usb(
+                Operation::ReadReport,
+                Some((w_value & 0xFF) as u8),
+            ))
+    }
+
+    fn read_string_descriptor(&self, index: u8) -> Result<String> {
+        let operation = Operation::ReadString(index);
+        let mut data = [0u8; 256];
+
+        let len = self
+            .handle
+            .read_control(
+                rusb::request_type(
+                    rusb::Direction::In,
+                    rusb::RequestType::Standard,
+                    rusb::Recipient::Device,
+                ),
+                rusb::constants::LIBUSB_REQUEST_GET_DESCRIPTOR,
+                (rusb::constants::LIBUSB_DT_STRING as u16) << 8 | index as u16,
+                0,
+                &mut data,
+                self.timeout,
+            )
+            .map_err(BlinkStickError::usb(operation, None))?;
+
+        if len <= 2 {
+            return Err(BlinkStickError::invalid_response(
+                operation,
+                None,
+                "descriptor is empty",
+            ));
+        }
+
+        // Convert UTF-16LE to String
+        let utf16_chars: Vec<u16> = data[2..len]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+
+        String::from_utf16(&utf16_chars)
+            .map_err(|e| BlinkStickError::invalid_response(operation, None, e.to_string()))
+    }
+
+    fn device_version(&self) -> Result<u16> {
+        let version = self
+            .handle
+            .device()
+            .device_descriptor()
+            .map_err(BlinkStickError::usb(Operation::ReadDescriptor, None))?
+            .device_version();
+
+        Ok(version_to_bcd(version))
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+impl Drop for UsbTransport {
+    fn drop(&mut self) {
+        let _ = self.handle.release_interface(0);
+
+        if self.reattach_kernel_driver {
+            let _ = self.handle.attach_kernel_driver(0);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! BlinkStick hardware variants and their capabilities.
+
+use serde::Serialize;
+use std::fmt;
+
+/// The BlinkStick model a device identifies as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum BlinkStickVariant {
+    BlinkStick,
+    Pro,
+    Square,
+    Strip,
+    Nano,
+    Flex,
+    Unknown,
+}
+
+impl BlinkStickVariant {
+    /// Identify a device from its serial number and bcdDevice release number
+    ///
+    /// The digit before the last dot of the serial (`BS012345-3.0`) gives the
+    /// hardware generation. Generation 3 devices share it and are told apart by
+    /// the release number.
+    pub fn detect(serial: &str, version: u16) -> Self {
+        let major = serial
+            .rsplit_once('-')
+            .and_then(|(_, revision)| revision.split('.').next());
+
+        match major {
+            Some("1") => Self::BlinkStick,
+            Some("2") => Self::Pro,
+            Some("3") => match version {
+                0x0200 => Self::Square,
+                0x0201 => Self::Strip,
+                0x0202 => Self::Nano,
+                0x0203 => Self::Flex,
+                _ => Self::Unknown,
+            },
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Human readable model name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BlinkStick => "BlinkStick",
+            Self::Pro => "BlinkStick Pro",
+            Self::Square => "BlinkStick Square",
+            Self::Strip => "BlinkStick Strip",
+            Self::Nano => "BlinkStick Nano",
+            Self::Flex => "BlinkStick Flex",
+            Self::Unknown => "Unknown BlinkStick",
+        }
+    }
+
+    /// Number of LEDs the model ships with on each channel
+    pub fn default_led_count(self) -> u8 {
+        match self {
+            Self::BlinkStick | Self::Unknown => 1,
+            Self::Pro => 64,
+            Self::Square | Self::Strip => 8,
+            Self::Nano => 2,
+            Self::Flex => 32,
+        }
+    }
+
+    /// Number of output channels
+    pub fn channel_count(self) -> u8 {
+        match self {
+            Self::Pro | Self::Unknown => 3,
+            _ => 1,
+        }
+    }
+
+    /// Whether the multi-LED reports 6-9 are understood
+    pub fn supports_multi_led(self) -> bool {
+        self != Self::BlinkStick
+    }
+
+    /// Whether single LEDs beyond the first can be addressed
+    pub fn supports_indexed_color(self) -> bool {
+        self != Self::BlinkStick
+    }
+
+    /// Whether the output mode report can be read and changed
+    pub fn supports_mode(self) -> bool {
+        matches!(self, Self::Pro | Self::Unknown)
+    }
+
+    /// Whether the LED count report 0x81 is understood
+    pub fn supports_led_count(self) -> bool {
+        matches!(self, Self::Flex | Self::Unknown)
+    }
+}
+
+impl fmt::Display for BlinkStickVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,363 @@
+//! Notifications when BlinkSticks are plugged in or removed.
+//!
+//! `DeviceWatcher` uses libusb hotplug callbacks where the platform supports
+//! them and falls back to polling `BlinkStick::find_devices` otherwise.
+
+use crate::{
+    BlinkStick, BlinkStickError, DeviceInfo, Operation, Result, BLINKSTICK_PRODUCT_ID,
+    BLINKSTICK_VENDOR_ID,
+};
+use rusb::{Context, Device, HotplugBuilder, UsbContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Interval used by `DeviceWatcher::new` when hotplug is unavailable
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// How long the event thread blocks before checking for shutdown
+const EVENT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A change in the set of connected BlinkSticks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    Attached(DeviceInfo),
+    /// Serial number of the removed device, or its USB path if the serial
+    /// could not be read while it was attached
+    Detached(String),
+}
+
+/// Identifies a device on the bus without opening it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceKey {
+    pub bus: u8,
+    pub address: u8,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl DeviceKey {
+    /// Read the key from the cached device descriptor
+    pub fn of(device: &Device<Context>) -> Result<Self> {
+        let descriptor = device
+            .device_descriptor()
+            .map_err(BlinkStickError::usb(Operation::ReadDescriptor, None))?;
+
+        Ok(Self {
+            bus: device.bus_number(),
+            address: device.address(),
+            vendor_id: descriptor.vendor_id(),
+            product_id: descriptor.product_id(),
+        })
+    }
+}
+
+/// Background watcher yielding `DeviceEvent`s
+///
+/// Devices already connected when the watcher starts are reported as
+/// attached. The watcher stops when dropped.
+pub struct DeviceWatcher {
+    events: Receiver<DeviceEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DeviceWatcher {
+    /// Watch using hotplug callbacks, or polling if hotplug is unsupported
+    pub fn new() -> Result<Self> {
+        // rusb::has_hotplug panics if libusb cannot be initialised
+        Context::new().map_err(BlinkStickError::usb(Operation::Enumerate, None))?;
+
+        if rusb::has_hotplug() {
+            Self::hotplug()
+        } else {
+            Ok(Self::polling(DEFAULT_POLL_INTERVAL))
+        }
+    }
+
+    /// Watch using libusb hotplug callbacks
+    pub fn hotplug() -> Result<Self> {
+        Context::new().map_err(BlinkStickError::usb(Operation::Hotplug, None))?;
+
+        if !rusb::has_hotplug() {
+            return Err(BlinkStickError::Usb {
+                operation: Operation::Hotplug,
+                report_id: None,
+                source: rusb::Error::NotSupported,
+            });
+        }
+
+        let (events, receiver) = mpsc::channel();
+        let (started, startup) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            run_hotplug(events, started, thread_stop);
+        });
+
+        match startup.recv() {
+            Ok(Ok(())) => Ok(Self {
+                events: receiver,
+                stop,
+                thread: Some(thread),
+            }),
+            Ok(Err(err)) => {
+                let _ = thread.join();
+                Err(err)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(BlinkStickError::Usb {
+                    operation: Operation::Hotplug,
+                    report_id: None,
+                    source: rusb::Error::Other,
+                })
+            }
+        }
+    }
+
+    /// Watch by enumerating the bus every `interval`
+    ///
+    /// Only newly arrived devices are opened to read their strings.
+    pub fn polling(interval: Duration) -> Self {
+        Self::polling_with(
+            interval,
+            || {
+                BlinkStick::find_devices()?
+                    .into_iter()
+                    .map(|device| Ok((DeviceKey::of(&device)?, device)))
+                    .collect()
+            },
+            DeviceInfo::from_device,
+        )
+    }
+
+    /// Watch by calling `enumerate` every `interval`
+    ///
+    /// `enumerate` lists the connected devices by key without opening them.
+    /// `describe` is only called for keys missing from the previous round; a
+    /// device it fails on is retried next round. Failed enumerations are
+    /// skipped.
+    pub fn polling_with<T, F, D>(interval: Duration, mut enumerate: F, mut describe: D) -> Self
+    where
+        T: 'static,
+        F: FnMut() -> Result<Vec<(DeviceKey, T)>> + Send + 'static,
+        D: FnMut(&T) -> Result<DeviceInfo> + Send + 'static,
+    {
+        let (events, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let thread = thread::spawn(move || {
+            let mut tracker = Tracker::default();
+
+            while !thread_stop.load(Ordering::Relaxed) {
+                if let Ok(devices) = enumerate() {
+                    for event in tracker.update(devices, &mut describe) {
+                        if events.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                sleep_until_stopped(&thread_stop, interval);
+            }
+        });
+
+        Self {
+            events: receiver,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Block until the next event; `None` once the watcher has stopped
+    pub fn recv(&self) -> Option<DeviceEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<DeviceEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Return the next event if one is pending
+    pub fn try_recv(&self) -> Option<DeviceEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl Iterator for DeviceWatcher {
+    type Item = DeviceEvent;
+
+    fn next(&mut self) -> Option<DeviceEvent> {
+        self.recv()
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Turns successive enumerations into attach/detach events
+#[derive(Default)]
+struct Tracker {
+    known: HashMap<DeviceKey, DeviceInfo>,
+}
+
+impl Tracker {
+    fn update<T, D>(&mut self, devices: Vec<(DeviceKey, T)>, mut describe: D) -> Vec<DeviceEvent>
+    where
+        D: FnMut(&T) -> Result<DeviceInfo>,
+    {
+        let current: HashMap<DeviceKey, T> = devices.into_iter().collect();
+        let mut events = Vec::new();
+
+        let mut gone: Vec<DeviceKey> = self
+            .known
+            .keys()
+            .filter(|key| !current.contains_key(key))
+            .copied()
+            .collect();
+        gone.sort();
+
+        for key in gone {
+            if let Some(info) = self.known.remove(&key) {
+                events.push(DeviceEvent::Detached(detached_id(&info)));
+            }
+        }
+
+        let mut arrived: Vec<(&DeviceKey, &T)> = current
+            .iter()
+            .filter(|(key, _)| !self.known.contains_key(key))
+            .collect();
+        arrived.sort_by_key(|(key, _)| **key);
+
+        for (key, device) in arrived {
+            if let Ok(info) = describe(device) {
+                events.push(DeviceEvent::Attached(info.clone()));
+                self.known.insert(*key, info);
+            }
+        }
+
+        events
+    }
+
+    fn remove(&mut self, bus: u8, address: u8) -> Option<DeviceInfo> {
+        let key = *self
+            .known
+            .keys()
+            .find(|key| key.bus == bus && key.address == address)?;
+        self.known.remove(&key)
+    }
+}
+
+fn detached_id(info: &DeviceInfo) -> String {
+    info.serial.clone().unwrap_or_else(|| info.path.to_string())
+}
+
+fn sleep_until_stopped(stop: &AtomicBool, duration: Duration) {
+    let mut remaining = duration;
+
+    while !remaining.is_zero() && !stop.load(Ordering::Relaxed) {
+        let step = remaining.min(EVENT_TIMEOUT);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
+
+enum RawEvent {
+    Arrived(Device<Context>),
+    Left(Device<Context>),
+}
+
+struct HotplugForwarder {
+    raw: Sender<RawEvent>,
+}
+
+impl rusb::Hotplug<Context> for HotplugForwarder {
+    // Descriptors cannot be read from inside the callback, so defer the work
+    fn device_arrived(&mut self, device: Device<Context>) {
+        let _ = self.raw.send(RawEvent::Arrived(device));
+    }
+
+    fn device_left(&mut self, device: Device<Context>) {
+        let _ = self.raw.send(RawEvent::Left(device));
+    }
+}
+
+fn run_hotplug(events: Sender<DeviceEvent>, started: Sender<Result<()>>, stop: Arc<AtomicBool>) {
+    let (raw_sender, raw) = mpsc::channel();
+
+    let registration = Context::new().and_then(|context| {
+        let mut builder = HotplugBuilder::new();
+        builder
+            .vendor_id(BLINKSTICK_VENDOR_ID)
+            .product_id(BLINKSTICK_PRODUCT_ID)
+            .enumerate(true);
+
+        let registration =
+            builder.register(&context, Box::new(HotplugForwarder { raw: raw_sender }))?;
+
+        Ok((context, registration))
+    });
+
+    let (context, _registration) = match registration {
+        Ok(registration) => {
+            let _ = started.send(Ok(()));
+            registration
+        }
+        Err(err) => {
+            let _ = started.send(Err(BlinkStickError::Usb {
+                operation: Operation::Hotplug,
+                report_id: None,
+                source: err,
+            }));
+            return;
+        }
+    };
+
+    let mut tracker = Tracker::default();
+
+    while !stop.load(Ordering::Relaxed) {
+        if context.handle_events(Some(EVENT_TIMEOUT)).is_err() {
+            thread::sleep(EVENT_TIMEOUT);
+        }
+
+        while let Ok(event) = raw.try_recv() {
+            let event = match event {
+                RawEvent::Arrived(device) => {
+                    match DeviceKey::of(&device)
+                        .and_then(|key| Ok((key, DeviceInfo::from_device(&device)?)))
+                    {
+                        Ok((key, info)) => {
+                            tracker.known.insert(key, info.clone());
+                            DeviceEvent::Attached(info)
+                        }
+                        Err(_) => continue,
+                    }
+                }
+                RawEvent::Left(device) => {
+                    match tracker.remove(device.bus_number(), device.address()) {
+                        Some(info) => DeviceEvent::Detached(detached_id(&info)),
+                        None => continue,
+                    }
+                }
+            };
+
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
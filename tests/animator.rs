@@ -0,0 +1,125 @@
+use blinkstick::{
+    Animator, BlinkStick, BlinkStickVariant, EndState, RgbColor, SimulatedBlinkStick,
+};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn animator(device: &SimulatedBlinkStick) -> Animator<SimulatedBlinkStick> {
+    Animator::new(BlinkStick::new(device.clone())).frame_interval(Duration::from_millis(5))
+}
+
+fn fill(color: RgbColor) -> impl FnMut(Duration, &mut [RgbColor]) + Send + 'static {
+    move |_, leds| leds.fill(color.clone())
+}
+
+#[test]
+fn stop_switches_the_leds_off_and_returns_the_device() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let animation = animator(&device).start(fill(RgbColor::new(0, 0, 255)));
+
+    thread::sleep(Duration::from_millis(40));
+    assert_eq!(device.leds(0), vec![RgbColor::new(0, 0, 255); 8]);
+
+    let blinkstick = animation.stop().unwrap();
+    let frames = device.reports().len();
+
+    assert!(frames > 2);
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+    assert_eq!(blinkstick.variant(), BlinkStickVariant::Strip);
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(device.reports().len(), frames);
+}
+
+#[test]
+fn pause_freezes_the_animation_until_resumed() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let animation = animator(&device).start(fill(RgbColor::new(1, 1, 1)));
+
+    thread::sleep(Duration::from_millis(20));
+    animation.pause();
+    assert!(animation.is_paused());
+    thread::sleep(Duration::from_millis(20));
+
+    let frames = device.reports().len();
+    thread::sleep(Duration::from_millis(40));
+    assert_eq!(device.reports().len(), frames);
+
+    animation.resume();
+    thread::sleep(Duration::from_millis(40));
+    assert!(device.reports().len() > frames);
+}
+
+#[test]
+fn replace_restarts_the_clock_with_the_new_effect() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Nano);
+    let animation = animator(&device).start(fill(RgbColor::new(255, 0, 0)));
+    thread::sleep(Duration::from_millis(40));
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = seen.clone();
+    animation.replace(move |elapsed, leds: &mut [RgbColor]| {
+        record.lock().unwrap().push(elapsed);
+        leds.fill(RgbColor::new(0, 255, 0));
+    });
+    thread::sleep(Duration::from_millis(40));
+
+    assert_eq!(device.leds(0), vec![RgbColor::new(0, 255, 0); 2]);
+    assert_eq!(seen.lock().unwrap()[0], Duration::ZERO);
+    assert!(animation.take_error().is_none());
+}
+
+#[test]
+fn end_state_can_hold_or_fill() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+
+    let animation = animator(&device)
+        .end_state(EndState::Hold)
+        .start(fill(RgbColor::new(3, 3, 3)));
+    thread::sleep(Duration::from_millis(20));
+    animation.stop().unwrap();
+    assert_eq!(device.leds(0), vec![RgbColor::new(3, 3, 3); 8]);
+
+    let animation = animator(&device)
+        .led_count(4)
+        .end_state(EndState::Color(RgbColor::new(0, 9, 0)))
+        .start(fill(RgbColor::new(3, 3, 3)));
+    thread::sleep(Duration::from_millis(20));
+    drop(animation);
+    assert_eq!(device.leds(0)[..4], vec![RgbColor::new(0, 9, 0); 4][..]);
+}
+
+#[test]
+fn failed_frames_are_reported_without_ending_the_animation() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let animation = animator(&device).start(fill(RgbColor::new(7, 7, 7)));
+
+    device.fail_next(1);
+    thread::sleep(Duration::from_millis(40));
+
+    assert!(animation.take_error().is_some());
+    assert_eq!(device.leds(0), vec![RgbColor::new(7, 7, 7); 8]);
+    animation.stop().unwrap();
+}
+
+#[test]
+fn failed_stop_still_returns_the_device() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let animation = animator(&device).start(fill(RgbColor::new(0, 0, 255)));
+
+    thread::sleep(Duration::from_millis(20));
+    device.set_connected(false);
+
+    let Err(err) = animation.stop() else {
+        panic!("stop should fail while the device is gone");
+    };
+    assert!(err.error().is_disconnected());
+
+    // Once the device is back the same handle finishes the job
+    device.set_connected(true);
+    let blinkstick = err.into_device().unwrap();
+    blinkstick.turn_off().unwrap();
+
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "async")]
+
+use blinkstick::{
+    AsyncBlinkStick, BlinkStick, BlinkStickError, BlinkStickVariant, Easing, RgbColor,
+    SimulatedBlinkStick,
+};
+use std::time::Duration;
+
+fn stick(device: &SimulatedBlinkStick) -> AsyncBlinkStick<SimulatedBlinkStick> {
+    AsyncBlinkStick::new(BlinkStick::new(device.clone()))
+}
+
+#[tokio::test]
+async fn colors_round_trip_through_the_worker() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = stick(&device);
+
+    blinkstick.set_color(&RgbColor::new(1, 2, 3)).await.unwrap();
+    assert_eq!(
+        blinkstick.get_color().await.unwrap(),
+        RgbColor::new(1, 2, 3)
+    );
+
+    let leds = vec![RgbColor::new(4, 5, 6); 8];
+    blinkstick.clone().set_colors(0, &leds).await.unwrap();
+
+    assert_eq!(device.leds(0), leds);
+    assert_eq!(blinkstick.variant(), BlinkStickVariant::Strip);
+}
+
+#[tokio::test]
+async fn spawn_reports_connect_errors() {
+    let result =
+        AsyncBlinkStick::<SimulatedBlinkStick>::spawn(|| Err(BlinkStickError::NoDeviceFound)).await;
+
+    assert!(matches!(result, Err(BlinkStickError::NoDeviceFound)));
+}
+
+#[tokio::test]
+async fn dropping_an_animation_stops_it() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+
+    let red = RgbColor::new(255, 0, 0);
+    let pulse = blinkstick.pulse(&red, 1000, 10);
+    assert!(tokio::time::timeout(Duration::from_millis(150), pulse)
+        .await
+        .is_err());
+
+    let sent = device.reports().len();
+    assert!(sent > 0 && sent < 20);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(device.reports().len(), sent);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn transfers_do_not_block_the_runtime() {
+    let blinkstick = stick(&SimulatedBlinkStick::new());
+
+    // A slow job on the worker must not starve timers on this thread
+    let slow = blinkstick.run(|_| {
+        std::thread::sleep(Duration::from_millis(200));
+        Ok(())
+    });
+    tokio::pin!(slow);
+
+    let ticked = tokio::select! {
+        _ = &mut slow => false,
+        _ = tokio::time::sleep(Duration::from_millis(10)) => true,
+    };
+
+    assert!(ticked);
+    slow.await.unwrap();
+}
+
+#[tokio::test]
+async fn pulse_matches_the_blocking_api() {
+    let red = RgbColor::new(200, 0, 0);
+
+    let blocking = SimulatedBlinkStick::new();
+    BlinkStick::new(blocking.clone())
+        .pulse_with(&red, 10, 5, Easing::Sine)
+        .unwrap();
+
+    let device = SimulatedBlinkStick::new();
+    stick(&device)
+        .pulse_with(&red, 10, 5, Easing::Sine)
+        .await
+        .unwrap();
+
+    assert_eq!(device.reports(), blocking.reports());
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(0, 0, 0)));
+}
+
+#[tokio::test]
+async fn pulse_rejects_zero_steps() {
+    let device = SimulatedBlinkStick::new();
+    let result = stick(&device).pulse(&RgbColor::new(200, 0, 0), 10, 0).await;
+
+    assert!(matches!(result, Err(BlinkStickError::InvalidArgument(_))));
+    assert!(device.reports().is_empty());
+}
@@ -0,0 +1,137 @@
+use blinkstick::effects::{Morph, Pulse};
+use blinkstick::{Easing, Effect, RgbColor};
+use std::time::Duration;
+
+const ALL: [Easing; 8] = [
+    Easing::Linear,
+    Easing::Quadratic,
+    Easing::Cubic,
+    Easing::Sine,
+    Easing::Exponential,
+    Easing::Bounce,
+    Easing::Elastic,
+    Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+];
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-3,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn every_curve_starts_at_zero_and_ends_at_one() {
+    for easing in ALL {
+        assert_close(easing.apply(0.0), 0.0);
+        assert_close(easing.apply(1.0), 1.0);
+        assert_close(easing.apply(-3.0), 0.0);
+        assert_close(easing.apply(7.0), 1.0);
+    }
+}
+
+#[test]
+fn linear_is_the_identity() {
+    for t in [0.1, 0.25, 0.5, 0.9] {
+        assert_close(Easing::Linear.apply(t), t);
+    }
+}
+
+#[test]
+fn quadratic_eases_in_and_out() {
+    assert_close(Easing::Quadratic.apply(0.25), 0.125);
+    assert_close(Easing::Quadratic.apply(0.5), 0.5);
+    assert_close(Easing::Quadratic.apply(0.75), 0.875);
+}
+
+#[test]
+fn cubic_eases_in_and_out() {
+    assert_close(Easing::Cubic.apply(0.25), 0.0625);
+    assert_close(Easing::Cubic.apply(0.5), 0.5);
+    assert_close(Easing::Cubic.apply(0.75), 0.9375);
+}
+
+#[test]
+fn sine_follows_half_a_cosine() {
+    assert_close(Easing::Sine.apply(0.25), 0.146_447);
+    assert_close(Easing::Sine.apply(0.5), 0.5);
+    assert_close(Easing::Sine.apply(0.75), 0.853_553);
+}
+
+#[test]
+fn exponential_stays_low_then_rises_steeply() {
+    assert_close(Easing::Exponential.apply(0.1), 0.001_953);
+    assert_close(Easing::Exponential.apply(0.5), 0.5);
+    assert_close(Easing::Exponential.apply(0.9), 0.998_047);
+}
+
+#[test]
+fn bounce_rebounds_before_settling() {
+    assert_close(Easing::Bounce.apply(0.2), 0.3025);
+    assert_close(Easing::Bounce.apply(1.0 / 2.75), 1.0);
+    assert_close(Easing::Bounce.apply(0.5), 0.765_625);
+    assert_close(Easing::Bounce.apply(0.9), 0.988_75);
+}
+
+#[test]
+fn elastic_overshoots_then_settles() {
+    assert_close(Easing::Elastic.apply(0.1), 1.25);
+    assert_close(Easing::Elastic.apply(0.5), 1.015_625);
+    assert!((0..=100)
+        .map(|i| Easing::Elastic.apply(i as f32 / 100.0))
+        .any(|v| v > 1.0));
+}
+
+#[test]
+fn cubic_bezier_matches_css_curves() {
+    // CSS `linear` and `ease`
+    for t in [0.1, 0.5, 0.8] {
+        assert_close(Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).apply(t), t);
+    }
+    assert_close(Easing::CubicBezier(0.25, 0.1, 0.25, 1.0).apply(0.5), 0.8024);
+
+    // Control points past the ends of the time axis are clamped
+    assert_close(
+        Easing::CubicBezier(-1.0, 0.0, 2.0, 1.0).apply(0.5),
+        Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).apply(0.5),
+    );
+}
+
+#[test]
+fn easing_parses_and_displays() {
+    for easing in ALL {
+        assert_eq!(easing.to_string().parse::<Easing>(), Ok(easing));
+    }
+
+    assert_eq!("Quad".parse::<Easing>(), Ok(Easing::Quadratic));
+    assert_eq!(
+        "cubic-bezier(0.4, 0, 0.2, 1)".parse::<Easing>(),
+        Ok(Easing::CubicBezier(0.4, 0.0, 0.2, 1.0))
+    );
+    assert!("cubic-bezier(0.4, 0)".parse::<Easing>().is_err());
+    assert!("wobble".parse::<Easing>().is_err());
+}
+
+#[test]
+fn effects_follow_their_easing() {
+    let mut leds = vec![RgbColor::default(); 1];
+    let mut pulse =
+        Pulse::new(RgbColor::new(200, 0, 0), Duration::from_millis(100)).easing(Easing::Cubic);
+
+    pulse.render(Duration::from_millis(25), &mut leds);
+    assert_eq!(leds[0], RgbColor::new(100, 0, 0));
+    pulse.render(Duration::from_millis(10), &mut leds);
+    assert_eq!(leds[0], RgbColor::new(6, 0, 0));
+
+    let mut morph = Morph::new(
+        vec![RgbColor::default()],
+        RgbColor::new(0, 200, 0),
+        Duration::from_millis(100),
+    )
+    .easing(Easing::Quadratic);
+
+    morph.render(Duration::from_millis(25), &mut leds);
+    assert_eq!(leds[0], RgbColor::new(0, 25, 0));
+}
@@ -0,0 +1,588 @@
+use blinkstick::effects::{
+    Blink, BlinkOptions, BlinkTarget, Chase, Direction, Fire, Morph, Motion, Pulse, Rainbow,
+    Sparkle, Twinkle,
+};
+use blinkstick::{BlinkStick, BlinkStickVariant, Effect, Engine, RgbColor, SimulatedBlinkStick};
+use std::time::Duration;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+fn render(
+    effect: &mut (impl Effect + ?Sized),
+    elapsed: Duration,
+    led_count: usize,
+) -> Vec<RgbColor> {
+    let mut leds = vec![RgbColor::default(); led_count];
+    effect.render(elapsed, &mut leds);
+    leds
+}
+
+/// Records the times it was rendered at
+struct Probe {
+    seen: Vec<Duration>,
+    length: Duration,
+}
+
+impl Effect for Probe {
+    fn render(&mut self, elapsed: Duration, leds: &mut [RgbColor]) {
+        self.seen.push(elapsed);
+        leds.fill(RgbColor::new(self.seen.len() as u8, 0, 0));
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(self.length)
+    }
+}
+
+#[test]
+fn pulse_fades_in_and_out_over_its_period() {
+    let mut pulse = Pulse::new(RgbColor::new(200, 100, 0), ms(100));
+
+    assert_eq!(render(&mut pulse, ms(0), 3), vec![RgbColor::default(); 3]);
+    assert_eq!(
+        render(&mut pulse, ms(25), 3),
+        vec![RgbColor::new(100, 50, 0); 3]
+    );
+    assert_eq!(
+        render(&mut pulse, ms(50), 3),
+        vec![RgbColor::new(200, 100, 0); 3]
+    );
+    assert_eq!(render(&mut pulse, ms(100), 3), vec![RgbColor::default(); 3]);
+    assert_eq!(pulse.duration(), Some(ms(100)));
+    assert_eq!(pulse.clone().times(3).duration(), Some(ms(300)));
+    assert_eq!(pulse.forever().duration(), None);
+}
+
+#[test]
+fn closures_are_effects() {
+    let mut effect = |elapsed: Duration, leds: &mut [RgbColor]| {
+        leds.fill(RgbColor::new(elapsed.as_millis() as u8, 0, 0));
+    };
+
+    assert_eq!(
+        render(&mut effect, ms(7), 2),
+        vec![RgbColor::new(7, 0, 0); 2]
+    );
+    assert_eq!(effect.duration(), None);
+}
+
+#[test]
+fn engine_renders_frames_until_the_effect_ends() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = BlinkStick::new(device.clone());
+    let mut probe = Probe {
+        seen: Vec::new(),
+        length: ms(60),
+    };
+
+    Engine::new(16)
+        .channel(2)
+        .fps(200)
+        .play(&blinkstick, &mut probe)
+        .unwrap();
+
+    let reports = device.reports();
+    assert_eq!(reports.len(), probe.seen.len());
+    assert!(reports.len() >= 3);
+    assert!(reports.iter().all(|r| r.report_id() == 7 && r.data[1] == 2));
+
+    assert_eq!(probe.seen[0], Duration::ZERO);
+    assert_eq!(*probe.seen.last().unwrap(), ms(60));
+    assert!(probe.seen.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn play_for_bounds_endless_effects() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = BlinkStick::new(device.clone());
+    let mut pulse = Pulse::new(RgbColor::new(0, 255, 0), ms(20)).forever();
+
+    Engine::for_device(&blinkstick)
+        .fps(100)
+        .play_for(&blinkstick, &mut pulse, ms(50))
+        .unwrap();
+
+    assert!(!device.reports().is_empty());
+}
+
+#[test]
+fn blinkstick_play_ends_a_pulse_with_the_leds_off() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = BlinkStick::new(device.clone());
+
+    blinkstick
+        .play(&mut Pulse::new(RgbColor::new(255, 0, 0), ms(40)))
+        .unwrap();
+
+    assert!(device.reports().len() > 1);
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+}
+
+#[test]
+fn lerp_blends_and_clamps() {
+    let black = RgbColor::default();
+    let white = RgbColor::new(255, 255, 255);
+
+    assert_eq!(black.lerp(&white, 0.5), RgbColor::new(128, 128, 128));
+    assert_eq!(white.lerp(&black, 0.25), RgbColor::new(191, 191, 191));
+    assert_eq!(black.lerp(&white, 2.0), white);
+    assert_eq!(white.lerp(&black, -1.0), white);
+}
+
+#[test]
+fn morph_blends_each_led_from_its_own_start() {
+    let from = vec![RgbColor::new(100, 0, 0), RgbColor::new(0, 100, 0)];
+    let mut morph = Morph::new(from, RgbColor::new(0, 0, 100), ms(100));
+
+    assert_eq!(
+        render(&mut morph, ms(0), 3),
+        vec![
+            RgbColor::new(100, 0, 0),
+            RgbColor::new(0, 100, 0),
+            RgbColor::new(0, 100, 0)
+        ]
+    );
+    assert_eq!(
+        render(&mut morph, ms(50), 2),
+        vec![RgbColor::new(50, 0, 50), RgbColor::new(0, 50, 50)]
+    );
+    assert_eq!(
+        render(&mut morph, ms(100), 2),
+        vec![RgbColor::new(0, 0, 100); 2]
+    );
+}
+
+#[test]
+fn morph_starts_from_the_color_read_back() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::BlinkStick);
+    BlinkStick::new(device.clone())
+        .set_color(&RgbColor::new(200, 0, 0))
+        .unwrap();
+    device.clear_reports();
+
+    let blinkstick = BlinkStick::new(device.clone());
+    blinkstick.morph(&RgbColor::new(0, 0, 200), ms(40)).unwrap();
+
+    let reports = device.reports();
+    assert_eq!(reports.first().unwrap().data, vec![1, 200, 0, 0]);
+    assert_eq!(reports.last().unwrap().data, vec![1, 0, 0, 200]);
+    assert!(reports
+        .iter()
+        .all(|r| r.data[1] as u16 + r.data[3] as u16 >= 199));
+}
+
+#[test]
+fn morph_starts_from_the_last_frame_on_strips() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = BlinkStick::new(device.clone());
+    let rainbowish: Vec<_> = (0..8).map(|i| RgbColor::new(i * 30, 0, 0)).collect();
+
+    blinkstick.set_colors(0, &rainbowish).unwrap();
+    blinkstick.morph(&RgbColor::new(0, 255, 0), ms(40)).unwrap();
+
+    let first = &device.reports()[1];
+    assert_eq!(&first.data[2..8], &[0, 0, 0, 0, 30, 0]);
+    assert_eq!(device.leds(0), vec![RgbColor::new(0, 255, 0); 8]);
+}
+
+#[test]
+fn blink_alternates_and_ends_dark() {
+    let red = RgbColor::new(255, 0, 0);
+    let mut blink = Blink::new(red.clone(), ms(100), ms(50)).times(2);
+
+    assert_eq!(blink.duration(), Some(ms(300)));
+    assert_eq!(render(&mut blink, ms(0), 2), vec![red.clone(); 2]);
+    assert_eq!(render(&mut blink, ms(99), 2), vec![red.clone(); 2]);
+    assert_eq!(render(&mut blink, ms(100), 2), vec![RgbColor::default(); 2]);
+    assert_eq!(render(&mut blink, ms(150), 2), vec![red.clone(); 2]);
+    assert_eq!(render(&mut blink, ms(300), 2), vec![RgbColor::default(); 2]);
+}
+
+#[test]
+fn blink_duty_cycle_splits_the_period() {
+    let red = RgbColor::new(255, 0, 0);
+    let mut blink = Blink::duty_cycle(red.clone(), ms(100), 0.25).forever();
+
+    assert_eq!(blink.duration(), None);
+    assert_eq!(render(&mut blink, ms(224), 1), vec![red]);
+    assert_eq!(render(&mut blink, ms(225), 1), vec![RgbColor::default()]);
+}
+
+#[test]
+fn blink_full_duty_cycle_stays_lit() {
+    let red = RgbColor::new(255, 0, 0);
+
+    for period in [100, 333, 777, 1001, 1_234_567] {
+        let mut blink = Blink::duty_cycle(red.clone(), ms(period), 1.0).times(2);
+
+        assert_eq!(blink.duration(), Some(ms(2 * period)));
+        assert_eq!(render(&mut blink, ms(0), 1), vec![red.clone()]);
+        assert_eq!(render(&mut blink, ms(period - 1), 1), vec![red.clone()]);
+        assert_eq!(render(&mut blink, ms(period + 1), 1), vec![red.clone()]);
+    }
+}
+
+#[test]
+fn blink_zero_duty_cycle_stays_dark() {
+    let mut blink = Blink::duty_cycle(RgbColor::new(255, 0, 0), ms(333), 0.0).times(2);
+
+    assert_eq!(blink.duration(), Some(ms(666)));
+    for at in [0, 100, 332, 333, 500] {
+        assert_eq!(render(&mut blink, ms(at), 1), vec![RgbColor::default()]);
+    }
+}
+
+#[test]
+fn blink_leaves_the_first_led_off() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::BlinkStick);
+    let blinkstick = BlinkStick::new(device.clone());
+
+    blinkstick
+        .blink(&RgbColor::new(255, 0, 0), 2, ms(20), ms(20))
+        .unwrap();
+
+    let reports = device.reports();
+    assert!(reports.iter().any(|r| r.data == vec![1, 255, 0, 0]));
+    assert_eq!(reports.last().unwrap().data, vec![1, 0, 0, 0]);
+}
+
+#[test]
+fn blink_range_keeps_other_leds_and_restores() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = BlinkStick::new(device.clone());
+    let blue = RgbColor::new(0, 0, 255);
+    blinkstick.set_all_colors(0, 8, &blue).unwrap();
+
+    let mut blink = Blink::new(RgbColor::new(255, 0, 0), ms(20), ms(20)).times(2);
+    let options = BlinkOptions {
+        target: BlinkTarget::Range(2..4),
+        restore: true,
+        ..Default::default()
+    };
+    blinkstick.blink_with(&mut blink, &options).unwrap();
+
+    // LEDs are GRB triples after the reserved byte and the channel
+    let frames: Vec<_> = device
+        .reports()
+        .iter()
+        .skip(1)
+        .map(|r| r.data.clone())
+        .collect();
+    assert!(frames.iter().any(|f| f[8..14] == [0, 255, 0, 0, 255, 0]));
+    assert!(frames.iter().all(|f| f[2..8] == [0, 0, 255, 0, 0, 255]));
+    assert_eq!(device.leds(0), vec![blue; 8]);
+}
+
+#[test]
+fn blink_leaves_leds_outside_the_target_unchanged() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let shown: Vec<_> = (0..8).map(|i| RgbColor::new(i * 30, i, 255 - i)).collect();
+    BlinkStick::new(device.clone())
+        .set_colors(0, &shown)
+        .unwrap();
+
+    for target in [BlinkTarget::Led(3), BlinkTarget::Range(2..5)] {
+        device.clear_reports();
+        let leds = match &target {
+            BlinkTarget::Led(index) => *index as usize..*index as usize + 1,
+            BlinkTarget::Range(range) => range.start as usize..range.end as usize,
+            BlinkTarget::Channel => unreachable!(),
+        };
+
+        // A fresh handle has written nothing, so it must read the strip back
+        let blinkstick = BlinkStick::new(device.clone());
+        let mut blink = Blink::new(RgbColor::new(0, 255, 0), ms(20), ms(20));
+        let options = BlinkOptions {
+            target,
+            restore: true,
+            ..Default::default()
+        };
+        blinkstick.blink_with(&mut blink, &options).unwrap();
+
+        for report in device.reports() {
+            let frame: Vec<_> = report.data[2..]
+                .chunks_exact(3)
+                .map(|grb| RgbColor::new(grb[1], grb[0], grb[2]))
+                .collect();
+            for index in (0..leds.end).filter(|index| !leds.contains(index)) {
+                assert_eq!(frame[index], shown[index]);
+            }
+        }
+        assert_eq!(device.leds(0), shown);
+    }
+}
+
+#[test]
+fn blink_channel_turns_every_led_off_afterwards() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Flex)
+        .with_channels(&[12]);
+    let blinkstick = BlinkStick::new(device.clone());
+    blinkstick.set_color(&RgbColor::new(0, 0, 255)).unwrap();
+
+    let mut blink = Blink::new(RgbColor::new(255, 0, 0), ms(20), ms(20));
+    let options = BlinkOptions {
+        target: BlinkTarget::Channel,
+        ..Default::default()
+    };
+    blinkstick.blink_with(&mut blink, &options).unwrap();
+
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 12]);
+}
+
+#[test]
+fn from_hsv_covers_the_color_wheel() {
+    assert_eq!(RgbColor::from_hsv(0.0, 1.0, 1.0), RgbColor::new(255, 0, 0));
+    assert_eq!(
+        RgbColor::from_hsv(120.0, 1.0, 1.0),
+        RgbColor::new(0, 255, 0)
+    );
+    assert_eq!(
+        RgbColor::from_hsv(240.0, 1.0, 1.0),
+        RgbColor::new(0, 0, 255)
+    );
+    assert_eq!(
+        RgbColor::from_hsv(60.0, 1.0, 0.5),
+        RgbColor::new(128, 128, 0)
+    );
+    assert_eq!(
+        RgbColor::from_hsv(-60.0, 1.0, 1.0),
+        RgbColor::new(255, 0, 255)
+    );
+    assert_eq!(
+        RgbColor::from_hsv(420.0, 0.0, 1.0),
+        RgbColor::new(255, 255, 255)
+    );
+}
+
+#[test]
+fn rainbow_cycle_paints_the_whole_strip_one_hue() {
+    let mut rainbow = Rainbow::cycle(ms(300));
+
+    assert_eq!(rainbow.duration(), None);
+    assert_eq!(
+        render(&mut rainbow, ms(0), 4),
+        vec![RgbColor::new(255, 0, 0); 4]
+    );
+    assert_eq!(
+        render(&mut rainbow, ms(100), 4),
+        vec![RgbColor::new(0, 255, 0); 4]
+    );
+    assert_eq!(
+        render(&mut rainbow, ms(500), 4),
+        vec![RgbColor::new(0, 0, 255); 4]
+    );
+}
+
+#[test]
+fn moving_rainbow_travels_along_the_strip() {
+    let mut rainbow = Rainbow::moving(ms(300)).brightness(0.5);
+    let first = render(&mut rainbow, ms(0), 3);
+
+    assert_eq!(
+        first,
+        vec![
+            RgbColor::new(128, 0, 0),
+            RgbColor::new(0, 0, 128),
+            RgbColor::new(0, 128, 0)
+        ]
+    );
+
+    // One third of the period later every color moved up one LED
+    let next = render(&mut rainbow, ms(100), 3);
+    assert_eq!(next[1..], first[..2]);
+
+    let mut reverse = Rainbow::moving(ms(300))
+        .brightness(0.5)
+        .direction(Direction::Reverse);
+    let next = render(&mut reverse, ms(100), 3);
+    assert_eq!(next[..2], render(&mut reverse, ms(0), 3)[1..]);
+}
+
+/// Brightness of each LED as a percentage of `full`
+fn levels(leds: &[RgbColor], full: u8) -> Vec<u32> {
+    leds.iter()
+        .map(|led| (led.r as u32 * 100 + full as u32 / 2) / full as u32)
+        .collect()
+}
+
+#[test]
+fn chase_moves_a_dot_and_wraps() {
+    let mut chase = Chase::new(RgbColor::new(200, 0, 0), ms(10));
+
+    assert_eq!(levels(&render(&mut chase, ms(0), 4), 200), [100, 0, 0, 0]);
+    assert_eq!(levels(&render(&mut chase, ms(25), 4), 200), [0, 0, 100, 0]);
+    assert_eq!(levels(&render(&mut chase, ms(40), 4), 200), [100, 0, 0, 0]);
+
+    let mut reverse = chase.direction(Direction::Reverse);
+    assert_eq!(
+        levels(&render(&mut reverse, ms(10), 4), 200),
+        [0, 0, 100, 0]
+    );
+}
+
+#[test]
+fn chase_tail_fades_behind_the_head() {
+    let mut chase = Chase::new(RgbColor::new(200, 0, 0), ms(10)).tail(3);
+
+    // The tail only grows as the dot travels
+    assert_eq!(
+        levels(&render(&mut chase, ms(10), 6), 200),
+        [75, 100, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        levels(&render(&mut chase, ms(30), 6), 200),
+        [25, 50, 75, 100, 0, 0]
+    );
+    assert_eq!(
+        levels(&render(&mut chase, ms(60), 6), 200),
+        [100, 0, 0, 25, 50, 75]
+    );
+}
+
+#[test]
+fn scanner_bounces_between_the_ends() {
+    let mut scanner = Chase::scanner(RgbColor::new(200, 0, 0), ms(10)).tail(1);
+    let heads: Vec<_> = (0..8)
+        .map(|step| {
+            let leds = levels(&render(&mut scanner, ms(step * 10), 4), 200);
+            leds.iter().position(|&level| level == 100).unwrap()
+        })
+        .collect();
+
+    assert_eq!(heads, [0, 1, 2, 3, 2, 1, 0, 1]);
+    assert_eq!(
+        levels(&render(&mut scanner, ms(40), 4), 200),
+        [0, 0, 100, 50]
+    );
+}
+
+#[test]
+fn marquee_lights_every_nth_led_and_shifts() {
+    let mut marquee = Chase::marquee(RgbColor::new(200, 0, 0), 3, ms(10));
+
+    assert_eq!(
+        levels(&render(&mut marquee, ms(0), 7), 200),
+        [100, 0, 0, 100, 0, 0, 100]
+    );
+    assert_eq!(
+        levels(&render(&mut marquee, ms(10), 7), 200),
+        [0, 100, 0, 0, 100, 0, 0]
+    );
+
+    let mut bouncing = Chase::marquee(RgbColor::new(200, 0, 0), 3, ms(10)).motion(Motion::Bounce);
+    assert_eq!(
+        levels(&render(&mut bouncing, ms(30), 7), 200),
+        [0, 100, 0, 0, 100, 0, 0]
+    );
+}
+
+#[test]
+fn seeded_effects_are_reproducible() {
+    let frames = |mut effect: Box<dyn Effect>| {
+        (0..50)
+            .map(|frame| render(&mut *effect, ms(frame * 40), 16))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        frames(Box::new(Fire::new().seed(7))),
+        frames(Box::new(Fire::new().seed(7)))
+    );
+    assert_ne!(
+        frames(Box::new(Fire::new().seed(7))),
+        frames(Box::new(Fire::new().seed(8)))
+    );
+    assert_eq!(
+        frames(Box::new(Twinkle::colorful().seed(7))),
+        frames(Box::new(Twinkle::colorful().seed(7)))
+    );
+    assert_eq!(
+        frames(Box::new(Sparkle::new(RgbColor::new(0, 0, 80)).seed(7))),
+        frames(Box::new(Sparkle::new(RgbColor::new(0, 0, 80)).seed(7)))
+    );
+}
+
+#[test]
+fn seeded_effects_do_not_depend_on_the_frame_rate() {
+    let mut smooth = Fire::new().seed(3);
+    for frame in 0..100 {
+        render(&mut smooth, ms(frame * 10), 10);
+    }
+
+    let mut choppy = Fire::new().seed(3);
+    render(&mut choppy, ms(500), 10);
+
+    assert_eq!(
+        render(&mut smooth, ms(1000), 10),
+        render(&mut choppy, ms(1000), 10)
+    );
+
+    // Going back in time replays from the start
+    let later = render(&mut choppy, ms(300), 10);
+    assert_eq!(later, render(&mut Fire::new().seed(3), ms(300), 10));
+}
+
+#[test]
+fn fire_burns_from_the_chosen_end() {
+    let forward = render(&mut Fire::new().seed(1), ms(2000), 12);
+    let mut reverse = render(
+        &mut Fire::new().seed(1).direction(Direction::Reverse),
+        ms(2000),
+        12,
+    );
+    reverse.reverse();
+
+    assert_eq!(forward, reverse);
+    assert!(forward[..3].iter().any(|led| led.r > 0));
+    // Fire only ever shows black, red, orange, yellow and white
+    assert!(forward
+        .iter()
+        .all(|led| led.b == 0 || led.r == 255 && led.g == 255));
+}
+
+#[test]
+fn twinkle_and_sparkle_stay_within_their_colors() {
+    let gold = RgbColor::new(200, 160, 0);
+    let mut twinkle = Twinkle::new(gold.clone()).density(0.2).seed(5);
+    let mut sparkle = Sparkle::new(RgbColor::new(0, 0, 100)).density(0.2).seed(5);
+    let mut lit = 0;
+
+    for frame in 0..100 {
+        for led in render(&mut twinkle, ms(frame * 20), 8) {
+            assert!(led.r <= gold.r && led.g <= gold.g && led.b == 0);
+            lit += (led.r > 0) as usize;
+        }
+
+        for led in render(&mut sparkle, ms(frame * 20), 8) {
+            assert!(led.r == led.g && led.b >= 100);
+        }
+    }
+
+    assert!(lit > 0);
+}
+
+#[test]
+fn seeded_effect_sends_the_same_frames_to_a_device() {
+    let play = || {
+        let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+        let blinkstick = BlinkStick::new(device.clone());
+
+        // Intermediate frames depend on timing, the last one only on the seed
+        Engine::for_device(&blinkstick)
+            .play_for(
+                &blinkstick,
+                &mut Twinkle::colorful().density(0.3).seed(11),
+                ms(200),
+            )
+            .unwrap();
+
+        device.leds(0)
+    };
+
+    let leds = play();
+    assert!(leds.iter().any(|led| *led != RgbColor::default()));
+    assert_eq!(leds, play());
+}
@@ -0,0 +1,119 @@
+#![cfg(all(feature = "hidraw", target_os = "linux"))]
+
+use blinkstick::hidraw::{self, FeatureIo, HidrawTransport};
+use blinkstick::{BlinkStick, RgbColor};
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Records feature reports and answers reads with canned responses
+#[derive(Default)]
+struct MockIo {
+    written: RefCell<Vec<Vec<u8>>>,
+    responses: RefCell<Vec<Vec<u8>>>,
+}
+
+impl FeatureIo for MockIo {
+    fn set_feature(&self, data: &[u8]) -> io::Result<()> {
+        self.written.borrow_mut().push(data.to_vec());
+        Ok(())
+    }
+
+    fn get_feature(&self, data: &mut [u8]) -> io::Result<usize> {
+        let response = self.responses.borrow_mut().remove(0);
+        assert_eq!(response[0], data[0], "unexpected report ID requested");
+        data[..response.len()].copy_from_slice(&response);
+        Ok(response.len())
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("blinkstick-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn fake_node(sysfs: &Path, node: &str, uevent: &str) {
+    let device = sysfs.join(node).join("device");
+    fs::create_dir_all(&device).unwrap();
+    fs::write(device.join("uevent"), uevent).unwrap();
+}
+
+#[test]
+fn class_reports_carry_the_report_id_in_the_first_byte() {
+    let blinkstick = BlinkStick::new(HidrawTransport::new(MockIo::default()));
+
+    blinkstick
+        .set_colors(0, &[RgbColor::new(1, 2, 3), RgbColor::new(4, 5, 6)])
+        .unwrap();
+
+    let written = blinkstick.transport().io().written.borrow();
+    assert_eq!(written[0].len(), 2 + 8 * 3);
+    assert_eq!(&written[0][..8], &[6, 0, 2, 1, 3, 5, 4, 6]);
+}
+
+#[test]
+fn feature_reads_go_through_the_ioctl_layer() {
+    let io = MockIo::default();
+    io.responses.borrow_mut().push(vec![1, 10, 20, 30]);
+    io.responses.borrow_mut().push(vec![0x81, 12]);
+    let blinkstick = BlinkStick::new(HidrawTransport::new(io));
+
+    assert_eq!(blinkstick.get_color().unwrap(), RgbColor::new(10, 20, 30));
+    assert_eq!(blinkstick.get_led_count(0).unwrap(), 12);
+}
+
+#[test]
+fn serial_comes_from_sysfs_strings() {
+    let transport = HidrawTransport::new(MockIo::default()).with_strings(
+        None,
+        None,
+        Some("BS000123-3.0".to_string()),
+    );
+
+    assert_eq!(
+        BlinkStick::new(transport).get_serial().unwrap(),
+        "BS000123-3.0"
+    );
+}
+
+#[test]
+fn enumeration_filters_by_vendor_and_product() {
+    let sysfs = temp_dir("hidraw-sysfs");
+
+    fake_node(
+        &sysfs,
+        "hidraw1",
+        "HID_ID=0003:000020A0:000041E5\nHID_NAME=Agile Innovative Ltd BlinkStick\nHID_UNIQ=BS000001-3.0\n",
+    );
+    fake_node(
+        &sysfs,
+        "hidraw0",
+        "HID_ID=0003:0000046D:0000C52B\nHID_NAME=Logitech USB Receiver\nHID_UNIQ=\n",
+    );
+    // IDs that only match once truncated to 16 bits
+    fake_node(
+        &sysfs,
+        "hidraw2",
+        "HID_ID=0003:000120A0:000041E5\nHID_NAME=Not a BlinkStick\nHID_UNIQ=\n",
+    );
+    fake_node(
+        &sysfs,
+        "hidraw3",
+        "HID_ID=0003:000020A0:000141E5\nHID_NAME=Not a BlinkStick\nHID_UNIQ=\n",
+    );
+
+    let devices = hidraw::find_all_in(&sysfs, Path::new("/dev")).unwrap();
+
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].path, Path::new("/dev/hidraw1"));
+    assert_eq!(devices[0].serial.as_deref(), Some("BS000001-3.0"));
+    assert_eq!(
+        devices[0].product.as_deref(),
+        Some("Agile Innovative Ltd BlinkStick")
+    );
+
+    fs::remove_dir_all(&sysfs).unwrap();
+}
@@ -0,0 +1,154 @@
+use blinkstick::{
+    BlinkStick, BlinkStickError, BlinkStickVariant, Operation, ReconnectPolicy,
+    ResilientBlinkStick, RgbColor, SimulatedBlinkStick,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn connector(
+    device: &SimulatedBlinkStick,
+) -> impl FnMut() -> blinkstick::Result<BlinkStick<SimulatedBlinkStick>> + Send + 'static {
+    let device = device.clone();
+    move || {
+        if !device.is_connected() {
+            return Err(BlinkStickError::Usb {
+                operation: Operation::Open,
+                report_id: None,
+                source: rusb::Error::NoDevice,
+            });
+        }
+        Ok(BlinkStick::new(device.clone()))
+    }
+}
+
+fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_delay: Duration::from_millis(5),
+        max_delay: Duration::from_millis(20),
+        multiplier: 2.0,
+        max_attempts: Some(max_attempts),
+    }
+}
+
+#[test]
+fn reconnects_and_replays_the_last_frame() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let mut blinkstick = ResilientBlinkStick::with_connector(connector(&device))
+        .unwrap()
+        .with_policy(fast_policy(100));
+
+    let frame = vec![RgbColor::new(0, 0, 255); 8];
+    blinkstick.set_colors(0, &frame).unwrap();
+
+    device.set_connected(false);
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+
+    let replug = {
+        let device = device.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(30));
+            device.set_connected(true);
+        })
+    };
+
+    blinkstick
+        .set_color_at(0, 3, &RgbColor::new(255, 0, 0))
+        .unwrap();
+    replug.join().unwrap();
+
+    let mut expected = frame;
+    expected[3] = RgbColor::new(255, 0, 0);
+    assert_eq!(device.leds(0), expected);
+    assert!(blinkstick.is_connected());
+}
+
+#[test]
+fn gives_up_after_max_attempts() {
+    let device = SimulatedBlinkStick::new();
+    let mut blinkstick = ResilientBlinkStick::with_connector(connector(&device))
+        .unwrap()
+        .with_policy(fast_policy(3));
+
+    device.set_connected(false);
+
+    assert!(blinkstick.set_color(&RgbColor::new(1, 2, 3)).is_err());
+    assert!(!blinkstick.is_connected());
+}
+
+#[test]
+fn argument_errors_do_not_trigger_a_reconnect() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Square);
+    let mut blinkstick = ResilientBlinkStick::with_connector(connector(&device))
+        .unwrap()
+        .with_policy(fast_policy(1));
+
+    assert!(blinkstick
+        .set_colors(2, &vec![RgbColor::default(); 2])
+        .is_err());
+    assert!(blinkstick.is_connected());
+}
+
+#[test]
+fn backoff_grows_up_to_the_maximum() {
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(500),
+        multiplier: 2.0,
+        max_attempts: None,
+    };
+
+    assert_eq!(policy.delay(0), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(400));
+    assert_eq!(policy.delay(5), Duration::from_millis(500));
+}
+
+#[test]
+fn transfer_errors_on_an_attached_device_do_not_reconnect() {
+    let device = SimulatedBlinkStick::new();
+    let connects = Arc::new(AtomicUsize::new(0));
+    let mut connect = connector(&device);
+    let counter = connects.clone();
+
+    let mut blinkstick = ResilientBlinkStick::with_connector(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        connect()
+    })
+    .unwrap()
+    .with_policy(fast_policy(3));
+
+    device.fail_next(1);
+    let err = blinkstick.set_color(&RgbColor::new(1, 2, 3)).unwrap_err();
+
+    assert!(err.is_transient());
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert!(blinkstick.is_connected());
+    assert_eq!(device.reports().len(), 0);
+
+    device.fail_next_with(1, rusb::Error::Timeout);
+    assert!(blinkstick.set_color(&RgbColor::new(1, 2, 3)).is_err());
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn from_device_keeps_the_open_handle_until_it_disappears() {
+    let device = SimulatedBlinkStick::new();
+    let connects = Arc::new(AtomicUsize::new(0));
+    let mut connect = connector(&device);
+    let counter = connects.clone();
+
+    let mut blinkstick =
+        ResilientBlinkStick::from_device(BlinkStick::new(device.clone()), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            connect()
+        })
+        .with_policy(fast_policy(3));
+
+    blinkstick.set_color(&RgbColor::new(1, 2, 3)).unwrap();
+    assert_eq!(connects.load(Ordering::SeqCst), 0);
+
+    blinkstick.reconnect().unwrap();
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(1, 2, 3)));
+}
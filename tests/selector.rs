@@ -0,0 +1,118 @@
+use blinkstick::{BlinkStickVariant, DeviceCandidate, DeviceInfo, DevicePath, DeviceSelector};
+use std::cell::Cell;
+
+struct FakeDevice {
+    serial: &'static str,
+    name: &'static str,
+    path: DevicePath,
+    name_reads: Cell<usize>,
+}
+
+impl FakeDevice {
+    fn new(serial: &'static str, name: &'static str, path: &str) -> Self {
+        Self {
+            serial,
+            name,
+            path: path.parse().unwrap(),
+            name_reads: Cell::new(0),
+        }
+    }
+}
+
+impl DeviceCandidate for &FakeDevice {
+    fn serial(&self) -> Option<String> {
+        Some(self.serial.to_string())
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name_reads.set(self.name_reads.get() + 1);
+        Some(self.name.to_string()).filter(|n| !n.is_empty())
+    }
+
+    fn path(&self) -> Option<DevicePath> {
+        Some(self.path.clone())
+    }
+}
+
+fn devices() -> Vec<FakeDevice> {
+    vec![
+        FakeDevice::new("BS000001-3.0", "", "1-2"),
+        FakeDevice::new("BS000002-3.0", "desk-7", "1-4.1"),
+        FakeDevice::new("BS000003-2.0", "desk-8", "3-1.2.3"),
+    ]
+}
+
+#[test]
+fn first_selects_the_first_enumerated_device() {
+    let devices = devices();
+    let selected = DeviceSelector::First.select(&devices).unwrap();
+
+    assert_eq!(selected.serial, "BS000001-3.0");
+}
+
+#[test]
+fn selects_by_serial_name_and_path() {
+    let devices = devices();
+
+    let by_serial = DeviceSelector::Serial("BS000003-2.0".to_string());
+    assert_eq!(by_serial.select(&devices).unwrap().name, "desk-8");
+
+    let by_name = DeviceSelector::Name("desk-7".to_string());
+    assert_eq!(by_name.select(&devices).unwrap().serial, "BS000002-3.0");
+
+    let by_path = DeviceSelector::Path("3-1.2.3".parse().unwrap());
+    assert_eq!(by_path.select(&devices).unwrap().serial, "BS000003-2.0");
+
+    let missing = DeviceSelector::Serial("BS999999-3.0".to_string());
+    assert!(missing.select(&devices).is_none());
+}
+
+#[test]
+fn names_are_only_read_until_a_match_is_found() {
+    let devices = devices();
+
+    DeviceSelector::Name("desk-7".to_string())
+        .select(&devices)
+        .unwrap();
+    DeviceSelector::Serial("BS000003-2.0".to_string())
+        .select(&devices)
+        .unwrap();
+
+    let reads: Vec<usize> = devices.iter().map(|d| d.name_reads.get()).collect();
+    assert_eq!(reads, vec![1, 1, 0]);
+}
+
+#[test]
+fn device_paths_parse_and_display() {
+    let path: DevicePath = "1-2.3".parse().unwrap();
+
+    assert_eq!(path, DevicePath::new(1, vec![2, 3]));
+    assert_eq!(path.to_string(), "1-2.3");
+    assert!("1".parse::<DevicePath>().is_err());
+    assert!("1-a".parse::<DevicePath>().is_err());
+    assert!("1-".parse::<DevicePath>().is_err());
+}
+
+#[test]
+fn device_info_is_selectable_and_serializable() {
+    let info = DeviceInfo {
+        serial: Some("BS000002-3.0".to_string()),
+        manufacturer: Some("Agile Innovative Ltd".to_string()),
+        product: Some("BlinkStick".to_string()),
+        firmware_version: "2.0.1".to_string(),
+        variant: BlinkStickVariant::Strip,
+        bus: 1,
+        address: 7,
+        path: DevicePath::new(1, vec![4, 1]),
+        openable: true,
+    };
+
+    let selector = DeviceSelector::Path("1-4.1".parse().unwrap());
+    assert!(selector.matches(&info));
+    assert!(DeviceSelector::Serial("BS000002-3.0".to_string()).matches(&info));
+
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["path"], "1-4.1");
+    assert_eq!(json["variant"], "Strip");
+    assert_eq!(json["openable"], true);
+}
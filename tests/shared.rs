@@ -0,0 +1,131 @@
+use blinkstick::{BlinkStick, BlinkStickVariant, RgbColor, SharedBlinkStick, SimulatedBlinkStick};
+use std::thread;
+use std::time::Duration;
+
+fn shared(device: &SimulatedBlinkStick) -> SharedBlinkStick<SimulatedBlinkStick> {
+    SharedBlinkStick::new(BlinkStick::new(device.clone()))
+}
+
+#[test]
+fn shared_handles_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<SharedBlinkStick>();
+    assert_send_sync::<SharedBlinkStick<SimulatedBlinkStick>>();
+}
+
+#[test]
+fn concurrent_writers_each_end_on_their_last_frame() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Pro)
+        .with_channels(&[8, 8, 8]);
+    let blinkstick = shared(&device);
+    let writes_per_thread = 100;
+
+    let threads: Vec<_> = (0..3u8)
+        .map(|channel| {
+            let blinkstick = blinkstick.clone();
+            thread::spawn(move || {
+                for step in 0..writes_per_thread {
+                    let frame = vec![RgbColor::new(channel, step, 0); 8];
+                    blinkstick.set_colors(channel, &frame).unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    for channel in 0..3u8 {
+        let last = RgbColor::new(channel, writes_per_thread - 1, 0);
+        assert_eq!(device.leds(channel), vec![last; 8]);
+    }
+    assert!(device.reports().len() <= 3 * writes_per_thread as usize);
+}
+
+#[test]
+fn frames_queued_during_a_transfer_are_coalesced() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = shared(&device);
+
+    let writers = blinkstick.with_device(|_| {
+        // All writers queue their frame while the device is busy
+        (1..=3u8)
+            .map(|value| {
+                let blinkstick = blinkstick.clone();
+                let writer = thread::spawn(move || {
+                    blinkstick.set_colors(0, &vec![RgbColor::new(value, 0, 0); 8])
+                });
+                thread::sleep(Duration::from_millis(30));
+                writer
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for writer in writers {
+        writer.join().unwrap().unwrap();
+    }
+
+    assert_eq!(device.reports().len(), 1);
+    assert_eq!(device.leds(0), vec![RgbColor::new(3, 0, 0); 8]);
+}
+
+#[test]
+fn superseded_writers_see_the_failure_of_the_covering_transfer() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = shared(&device);
+
+    let writers = blinkstick.with_device(|_| {
+        device.fail_next(1);
+
+        (1..=3u8)
+            .map(|value| {
+                let blinkstick = blinkstick.clone();
+                let writer = thread::spawn(move || {
+                    blinkstick.set_colors(0, &vec![RgbColor::new(value, 0, 0); 8])
+                });
+                thread::sleep(Duration::from_millis(30));
+                writer
+            })
+            .collect::<Vec<_>>()
+    });
+
+    for writer in writers {
+        let err = writer.join().unwrap().unwrap_err();
+        assert!(err.is_transfer_error());
+    }
+
+    assert_eq!(device.reports().len(), 0);
+
+    blinkstick
+        .set_colors(0, &vec![RgbColor::new(4, 0, 0); 8])
+        .unwrap();
+    assert_eq!(device.leds(0), vec![RgbColor::new(4, 0, 0); 8]);
+}
+
+#[test]
+fn single_led_writes_are_not_dropped() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = shared(&device);
+
+    let threads: Vec<_> = (0..8u8)
+        .map(|index| {
+            let blinkstick = blinkstick.clone();
+            thread::spawn(move || {
+                blinkstick
+                    .set_color_at(0, index, &RgbColor::new(index, index, index))
+                    .unwrap();
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let expected: Vec<_> = (0..8u8).map(|i| RgbColor::new(i, i, i)).collect();
+    assert_eq!(device.leds(0), expected);
+    assert_eq!(device.reports().len(), 8);
+}
@@ -0,0 +1,456 @@
+use blinkstick::{
+    BlinkStick, BlinkStickBuilder, BlinkStickError, BlinkStickVariant, Easing, Mode, Operation,
+    RgbColor, SimulatedBlinkStick, Transport,
+};
+use std::time::Duration;
+
+fn stick(device: &SimulatedBlinkStick) -> BlinkStick<SimulatedBlinkStick> {
+    BlinkStick::new(device.clone())
+}
+
+#[test]
+fn set_color_round_trips_through_get_color() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+
+    blinkstick.set_color(&RgbColor::new(10, 20, 30)).unwrap();
+
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(10, 20, 30)));
+    assert_eq!(blinkstick.get_color().unwrap(), RgbColor::new(10, 20, 30));
+    assert_eq!(device.reports()[0].data, vec![1, 10, 20, 30]);
+}
+
+/// Answers every report 1 read with fixed bytes, independent of the simulator
+struct FixedColorReport([u8; 4]);
+
+impl Transport for FixedColorReport {
+    fn send_feature_report(&self, _data: &[u8]) -> blinkstick::Result<()> {
+        Ok(())
+    }
+
+    fn send_class_report(&self, _: u16, _: u16, _data: &[u8]) -> blinkstick::Result<()> {
+        Ok(())
+    }
+
+    fn get_feature_report(&self, data: &mut [u8]) -> blinkstick::Result<()> {
+        data.copy_from_slice(&self.0);
+        Ok(())
+    }
+
+    fn read_class_report(&self, _: u16, _: u16, _data: &mut [u8]) -> blinkstick::Result<usize> {
+        Ok(0)
+    }
+
+    fn read_string_descriptor(&self, _index: u8) -> blinkstick::Result<String> {
+        Ok("BS000001-3.0".to_string())
+    }
+
+    fn device_version(&self) -> blinkstick::Result<u16> {
+        Ok(0x0200)
+    }
+}
+
+#[test]
+fn get_color_reads_report_1_in_rgb_order() {
+    // Regression: red and green used to be swapped when reading report 1
+    let blinkstick = BlinkStick::new(FixedColorReport([1, 0x11, 0x22, 0x33]));
+
+    assert_eq!(
+        blinkstick.get_color().unwrap(),
+        RgbColor::new(0x11, 0x22, 0x33)
+    );
+}
+
+#[test]
+fn get_color_matches_a_color_written_as_grb() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Square);
+    let blinkstick = stick(&device);
+
+    blinkstick
+        .set_colors(0, &[RgbColor::new(10, 20, 30), RgbColor::new(1, 2, 3)])
+        .unwrap();
+
+    assert_eq!(blinkstick.get_color().unwrap(), RgbColor::new(10, 20, 30));
+}
+
+#[test]
+fn set_colors_uses_smallest_report_and_grb_order() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Pro)
+        .with_channels(&[8, 8, 8]);
+    let blinkstick = stick(&device);
+    let leds = vec![RgbColor::new(1, 2, 3), RgbColor::new(4, 5, 6)];
+
+    blinkstick.set_colors(2, &leds).unwrap();
+
+    let report = &device.reports()[0];
+    assert_eq!(report.report_id(), 6);
+    assert_eq!(report.data.len(), 2 + 8 * 3);
+    assert_eq!(&report.data[..8], &[0, 2, 2, 1, 3, 5, 4, 6]);
+    assert_eq!(&device.leds(2)[..2], &leds[..]);
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+}
+
+#[test]
+fn set_colors_selects_larger_reports_for_long_strips() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Flex)
+        .with_channels(&[64]);
+    let blinkstick = stick(&device);
+
+    blinkstick
+        .set_all_colors(0, 20, &RgbColor::new(9, 9, 9))
+        .unwrap();
+    blinkstick
+        .set_all_colors(0, 64, &RgbColor::new(7, 7, 7))
+        .unwrap();
+
+    let ids: Vec<u8> = device.reports().iter().map(|r| r.report_id()).collect();
+    assert_eq!(ids, vec![8, 9]);
+    assert_eq!(device.leds(0), vec![RgbColor::new(7, 7, 7); 64]);
+}
+
+#[test]
+fn get_colors_reads_back_what_another_handle_wrote() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Pro)
+        .with_channels(&[8, 20, 8]);
+    let leds: Vec<_> = (0..20).map(|i| RgbColor::new(i, 2 * i, 3 * i)).collect();
+
+    stick(&device).set_colors(1, &leds).unwrap();
+
+    let blinkstick = stick(&device);
+    assert_eq!(blinkstick.get_colors(1, 20).unwrap(), leds);
+    assert_eq!(blinkstick.get_colors(1, 3).unwrap(), &leds[..3]);
+    assert_eq!(
+        blinkstick.get_colors(0, 2).unwrap(),
+        vec![RgbColor::default(); 2]
+    );
+    assert_eq!(blinkstick.get_colors(1, 0).unwrap(), vec![]);
+}
+
+#[test]
+fn set_color_at_sends_the_indexed_report() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = stick(&device);
+
+    blinkstick
+        .set_color_at(2, 17, &RgbColor::new(10, 20, 30))
+        .unwrap();
+    blinkstick
+        .set_color_indexed(3, &RgbColor::new(40, 50, 60))
+        .unwrap();
+
+    let reports = device.reports();
+    assert_eq!(reports[0].w_value, 0x0305);
+    assert_eq!(reports[0].data, vec![5, 2, 17, 10, 20, 30]);
+    assert_eq!(reports[1].data, vec![5, 0, 3, 40, 50, 60]);
+    assert_eq!(device.led(2, 17), Some(RgbColor::new(10, 20, 30)));
+    assert_eq!(device.led(0, 3), Some(RgbColor::new(40, 50, 60)));
+    assert_eq!(device.led(0, 17), Some(RgbColor::default()));
+}
+
+#[test]
+fn set_color_at_uses_report_1_for_the_first_led() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = stick(&device);
+
+    blinkstick
+        .set_color_at(0, 0, &RgbColor::new(1, 2, 3))
+        .unwrap();
+    blinkstick
+        .set_color_at(1, 0, &RgbColor::new(4, 5, 6))
+        .unwrap();
+
+    let reports = device.reports();
+    assert_eq!(reports[0].data, vec![1, 1, 2, 3]);
+    assert_eq!(reports[1].data, vec![5, 1, 0, 4, 5, 6]);
+    assert!(blinkstick.set_color_at(3, 0, &RgbColor::default()).is_err());
+}
+
+#[test]
+fn led_count_is_read_and_written() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Flex)
+        .with_channels(&[8]);
+    let blinkstick = stick(&device);
+
+    assert_eq!(blinkstick.get_led_count(0).unwrap(), 8);
+
+    blinkstick.set_led_count(32).unwrap();
+
+    assert_eq!(blinkstick.get_led_count(0).unwrap(), 32);
+    assert!(blinkstick.set_led_count(0).is_err());
+}
+
+#[test]
+fn pulse_ends_with_the_led_off() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+
+    blinkstick.pulse(&RgbColor::new(200, 0, 0), 10, 5).unwrap();
+
+    let reports = device.reports();
+    assert_eq!(reports.len(), 10);
+    assert_eq!(reports[4].data, vec![1, 200, 0, 0]);
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(0, 0, 0)));
+}
+
+#[test]
+fn pulse_rejects_zero_steps() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+    let red = RgbColor::new(200, 0, 0);
+
+    assert!(matches!(
+        blinkstick.pulse(&red, 10, 0),
+        Err(BlinkStickError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        blinkstick.pulse_with(&red, 10, 0, Easing::Sine),
+        Err(BlinkStickError::InvalidArgument(_))
+    ));
+    assert!(device.reports().is_empty());
+}
+
+#[test]
+fn serial_is_read_from_the_string_descriptor() {
+    let device = SimulatedBlinkStick::new().with_serial("BS012345-3.0");
+
+    assert_eq!(stick(&device).get_serial().unwrap(), "BS012345-3.0");
+}
+
+#[test]
+fn info_blocks_hold_the_name_and_user_data() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+
+    blinkstick.set_name("desk-7").unwrap();
+    blinkstick.set_info_block2(&[0xDE, 0xAD]).unwrap();
+
+    assert_eq!(device.reports()[0].data.len(), 33);
+    assert_eq!(&device.info_block(1).unwrap()[..7], b"desk-7\0");
+    assert_eq!(device.info_block(0), None);
+    assert_eq!(device.info_block(3), None);
+    assert_eq!(blinkstick.name().unwrap(), "desk-7");
+    assert_eq!(
+        &blinkstick.get_info_block2().unwrap()[..3],
+        &[0xDE, 0xAD, 0]
+    );
+    assert!(blinkstick.set_info_block1(&[b'x'; 33]).is_err());
+}
+
+#[test]
+fn mode_is_read_and_written() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = stick(&device);
+
+    assert_eq!(blinkstick.get_mode().unwrap(), Mode::Normal);
+
+    blinkstick.set_mode(Mode::Ws2812).unwrap();
+
+    assert_eq!(device.reports()[0].data, vec![4, 2]);
+    assert_eq!(blinkstick.get_mode().unwrap(), Mode::Ws2812);
+
+    let square = stick(&SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Square));
+    assert!(square.set_mode(Mode::Inverse).is_err());
+}
+
+#[test]
+fn injected_failures_surface_as_errors() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = stick(&device);
+    let setup_transfers = device.transfer_count();
+
+    device.fail_next(1);
+    assert!(blinkstick.set_color(&RgbColor::new(1, 1, 1)).is_err());
+    assert!(blinkstick.set_color(&RgbColor::new(1, 1, 1)).is_ok());
+
+    device.set_connected(false);
+    assert!(blinkstick.get_color().is_err());
+    assert_eq!(device.transfer_count() - setup_transfers, 3);
+}
+
+#[test]
+fn variant_is_detected_from_serial_and_release_number() {
+    for variant in [
+        BlinkStickVariant::BlinkStick,
+        BlinkStickVariant::Pro,
+        BlinkStickVariant::Square,
+        BlinkStickVariant::Strip,
+        BlinkStickVariant::Nano,
+        BlinkStickVariant::Flex,
+    ] {
+        let device = SimulatedBlinkStick::new().with_variant(variant);
+        assert_eq!(stick(&device).variant(), variant);
+    }
+
+    assert_eq!(
+        BlinkStickVariant::detect("BS012345-3.0", 0x0299),
+        BlinkStickVariant::Unknown
+    );
+    assert_eq!(
+        BlinkStickVariant::detect("garbage", 0x0200),
+        BlinkStickVariant::Unknown
+    );
+}
+
+#[test]
+fn unsupported_operations_are_rejected_without_transfers() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::BlinkStick);
+    let blinkstick = stick(&device);
+    let leds = vec![RgbColor::new(1, 1, 1); 2];
+
+    assert!(blinkstick.set_colors(0, &leds).is_err());
+    assert!(blinkstick.set_color_indexed(1, &leds[0]).is_err());
+    assert!(blinkstick.set_colors(0, &leds[..1]).is_ok());
+
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Square);
+    let blinkstick = stick(&device);
+
+    assert!(blinkstick.set_colors(1, &leds).is_err());
+    assert!(blinkstick.set_led_count(16).is_err());
+    assert_eq!(blinkstick.get_led_count(0).unwrap(), 8);
+    assert!(device.reports().is_empty());
+}
+
+#[test]
+fn transfer_errors_keep_the_usb_cause_and_report() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = stick(&device);
+
+    device.fail_next_with(1, rusb::Error::Access);
+    let err = blinkstick
+        .set_colors(1, &vec![RgbColor::new(1, 2, 3); 4])
+        .unwrap_err();
+
+    assert!(err.is_permission_denied());
+    assert_eq!(err.usb_error(), Some(rusb::Error::Access));
+    assert_eq!(err.operation(), Some(Operation::SendReport));
+    assert_eq!(err.report_id(), Some(6));
+
+    device.fail_next(1);
+    let err = blinkstick.get_color().unwrap_err();
+
+    assert!(err.is_transient());
+    assert_eq!(err.operation(), Some(Operation::ReadReport));
+    assert_eq!(err.report_id(), Some(1));
+
+    device.set_connected(false);
+    let err = blinkstick.get_serial().unwrap_err();
+
+    assert!(err.is_disconnected());
+    assert_eq!(err.operation(), Some(Operation::ReadString(3)));
+}
+
+#[test]
+fn rejected_requests_are_not_transfer_errors() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Square);
+    let blinkstick = stick(&device);
+
+    let err = blinkstick.set_mode(Mode::Inverse).unwrap_err();
+    assert!(matches!(err, BlinkStickError::Unsupported { .. }));
+    assert!(!err.is_transfer_error());
+
+    let err = blinkstick.set_info_block1(&[0; 40]).unwrap_err();
+    assert!(matches!(err, BlinkStickError::InvalidArgument(_)));
+}
+
+#[test]
+fn transient_failures_are_retried_up_to_the_limit() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = BlinkStickBuilder::new()
+        .retries(2)
+        .retry_delay(Duration::ZERO)
+        .timeout(Duration::from_millis(250))
+        .build(device.clone());
+    let setup_transfers = device.transfer_count();
+
+    assert_eq!(device.timeout(), Some(Duration::from_millis(250)));
+
+    device.fail_next_with(2, rusb::Error::Busy);
+    blinkstick.set_color(&RgbColor::new(1, 2, 3)).unwrap();
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(1, 2, 3)));
+    assert_eq!(device.transfer_count() - setup_transfers, 3);
+
+    device.fail_next_with(3, rusb::Error::Timeout);
+    assert!(blinkstick.set_color(&RgbColor::new(4, 5, 6)).is_err());
+    assert_eq!(device.transfer_count() - setup_transfers, 6);
+}
+
+#[test]
+fn permanent_failures_are_not_retried() {
+    let device = SimulatedBlinkStick::new();
+    let blinkstick = BlinkStickBuilder::new()
+        .retries(5)
+        .retry_delay(Duration::ZERO)
+        .build(device.clone());
+    let setup_transfers = device.transfer_count();
+
+    device.fail_next_with(1, rusb::Error::Access);
+    assert!(blinkstick.get_color().is_err());
+
+    device.set_connected(false);
+    assert!(blinkstick.get_color().is_err());
+    assert_eq!(device.transfer_count() - setup_transfers, 2);
+}
+
+#[test]
+fn turn_off_on_close_blanks_what_was_lit() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Pro);
+    let blinkstick = BlinkStickBuilder::new()
+        .turn_off_on_close(true)
+        .build(device.clone());
+
+    blinkstick
+        .set_colors(1, &vec![RgbColor::new(9, 9, 9); 5])
+        .unwrap();
+    blinkstick.set_color(&RgbColor::new(1, 1, 1)).unwrap();
+    drop(blinkstick);
+
+    assert_eq!(device.led(0, 0), Some(RgbColor::default()));
+    assert_eq!(device.leds(1), vec![RgbColor::default(); 64]);
+    assert!(device
+        .reports()
+        .iter()
+        .all(|r| r.data[0] != 0 || r.data[1] != 2));
+
+    // Handing out the transport keeps the LEDs lit
+    let blinkstick = stick(&device).with_turn_off_on_close(true);
+    blinkstick.set_color(&RgbColor::new(5, 5, 5)).unwrap();
+    let _transport = blinkstick.into_transport();
+
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(5, 5, 5)));
+}
+
+#[test]
+fn turn_off_reaches_leds_lit_by_an_earlier_longer_frame() {
+    let device = SimulatedBlinkStick::new()
+        .with_variant(BlinkStickVariant::Flex)
+        .with_channels(&[20]);
+    let blinkstick = stick(&device);
+
+    // The short frame goes out in the 8 LED report and leaves LEDs 8..20 lit
+    blinkstick
+        .set_colors(0, &vec![RgbColor::new(9, 9, 9); 20])
+        .unwrap();
+    blinkstick
+        .set_colors(0, &vec![RgbColor::new(3, 3, 3); 2])
+        .unwrap();
+    blinkstick.turn_off().unwrap();
+
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 20]);
+    assert_eq!(
+        blinkstick.last_frame(0),
+        Some(vec![RgbColor::default(); 20])
+    );
+}
+
+#[test]
+fn devices_are_left_alone_on_drop_by_default() {
+    let device = SimulatedBlinkStick::new();
+    stick(&device).set_color(&RgbColor::new(5, 5, 5)).unwrap();
+
+    assert_eq!(device.led(0, 0), Some(RgbColor::new(5, 5, 5)));
+    assert_eq!(device.reports().len(), 1);
+}
@@ -0,0 +1,155 @@
+use blinkstick::timeline::Colors;
+use blinkstick::{
+    BlinkStick, BlinkStickVariant, Easing, Effect, Engine, Keyframe, Repeat, RgbColor,
+    SimulatedBlinkStick, Timeline,
+};
+use std::time::Duration;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// One line per sample time listing every LED as hex
+fn snapshot(timeline: &Timeline, times: &[u64], led_count: usize) -> String {
+    times
+        .iter()
+        .map(|&at| {
+            let leds: Vec<_> = timeline
+                .sample(ms(at), led_count)
+                .iter()
+                .map(|led| format!("{:02x}{:02x}{:02x}", led.r, led.g, led.b))
+                .collect();
+            format!("{:>5}ms {}", at, leds.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn alert() -> Timeline {
+    Timeline::new()
+        .hold(RgbColor::new(255, 0, 0), ms(2000))
+        .fade(RgbColor::new(255, 192, 0), ms(500), Easing::Linear)
+        .blink(RgbColor::new(0, 0, 255), 2, ms(100), ms(100))
+        .hold(RgbColor::new(255, 192, 0), ms(1000))
+}
+
+#[test]
+fn sequence_renders_each_keyframe_in_turn() {
+    let timeline = alert();
+
+    assert_eq!(timeline.total_duration(), ms(3900));
+    assert_eq!(timeline.duration(), Some(ms(3900)));
+    assert_eq!(
+        snapshot(
+            &timeline,
+            &[0, 1999, 2000, 2250, 2500, 2599, 2600, 2700, 2800, 2900, 3899, 5000],
+            2
+        ),
+        "    0ms ff0000 ff0000
+ 1999ms ff0000 ff0000
+ 2000ms ff0000 ff0000
+ 2250ms ff6000 ff6000
+ 2500ms 0000ff 0000ff
+ 2599ms 0000ff 0000ff
+ 2600ms 000000 000000
+ 2700ms 0000ff 0000ff
+ 2800ms 000000 000000
+ 2900ms ffc000 ffc000
+ 3899ms ffc000 ffc000
+ 5000ms ffc000 ffc000"
+    );
+}
+
+#[test]
+fn per_led_keyframes_fade_each_led_separately() {
+    let timeline = Timeline::new()
+        .fade(
+            vec![RgbColor::new(200, 0, 0), RgbColor::new(0, 200, 0)],
+            ms(100),
+            Easing::Linear,
+        )
+        .fade(RgbColor::new(0, 0, 200), ms(100), Easing::Quadratic);
+
+    assert_eq!(
+        snapshot(&timeline, &[0, 50, 100, 125, 150, 200], 3),
+        "    0ms 000000 000000 000000
+   50ms 640000 006400 000000
+  100ms c80000 00c800 000000
+  125ms af0019 00af19 000019
+  150ms 640064 006464 000064
+  200ms 0000c8 0000c8 0000c8"
+    );
+}
+
+#[test]
+fn loop_and_ping_pong_repeat_the_keyframes() {
+    let steps = Timeline::new()
+        .hold(RgbColor::new(10, 0, 0), ms(100))
+        .hold(RgbColor::new(20, 0, 0), ms(100))
+        .hold(RgbColor::new(30, 0, 0), ms(100));
+    let times = [0, 100, 200, 300, 400, 500, 600];
+
+    let looping = steps.clone().repeat(Repeat::Loop);
+    assert_eq!(looping.duration(), None);
+    assert_eq!(
+        snapshot(&looping, &times, 1),
+        "    0ms 0a0000
+  100ms 140000
+  200ms 1e0000
+  300ms 0a0000
+  400ms 140000
+  500ms 1e0000
+  600ms 0a0000"
+    );
+
+    let ping_pong = steps.repeat(Repeat::PingPong);
+    assert_eq!(ping_pong.duration(), None);
+    assert_eq!(
+        snapshot(&ping_pong, &[0, 150, 250, 350, 450, 550, 650], 1),
+        "    0ms 0a0000
+  150ms 140000
+  250ms 1e0000
+  350ms 1e0000
+  450ms 140000
+  550ms 0a0000
+  650ms 0a0000"
+    );
+}
+
+#[test]
+fn empty_and_zero_length_timelines_render_off() {
+    assert_eq!(
+        Timeline::new().sample(ms(100), 2),
+        vec![RgbColor::default(); 2]
+    );
+
+    let instant = Timeline::new()
+        .keyframe(Keyframe::fade(
+            Colors::Fill(RgbColor::new(1, 2, 3)),
+            Duration::ZERO,
+            Easing::Sine,
+        ))
+        .repeat(Repeat::Loop);
+    assert_eq!(instant.sample(ms(100), 1), vec![RgbColor::new(1, 2, 3)]);
+}
+
+#[test]
+fn timeline_plays_on_a_device_and_ends_on_the_last_keyframe() {
+    let device = SimulatedBlinkStick::new().with_variant(BlinkStickVariant::Strip);
+    let blinkstick = BlinkStick::new(device.clone());
+
+    let mut timeline = Timeline::new()
+        .hold(RgbColor::new(255, 0, 0), ms(20))
+        .fade(RgbColor::new(255, 192, 0), ms(40), Easing::Sine)
+        .blink(RgbColor::new(0, 0, 255), 2, ms(10), ms(10));
+
+    Engine::for_device(&blinkstick)
+        .play(&blinkstick, &mut timeline)
+        .unwrap();
+
+    assert!(device
+        .reports()
+        .iter()
+        .any(|report| report.data[2..5] == [0, 255, 0]));
+    assert_eq!(device.leds(0), vec![RgbColor::default(); 8]);
+}
@@ -0,0 +1,107 @@
+use blinkstick::selector::DevicePath;
+use blinkstick::{BlinkStickVariant, DeviceEvent, DeviceInfo, DeviceKey, DeviceWatcher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn info(serial: Option<&str>, address: u8) -> DeviceInfo {
+    DeviceInfo {
+        serial: serial.map(str::to_string),
+        manufacturer: None,
+        product: None,
+        firmware_version: "2.0.0".to_string(),
+        variant: BlinkStickVariant::Square,
+        bus: 1,
+        address,
+        path: DevicePath::new(1, vec![address]),
+        openable: serial.is_some(),
+    }
+}
+
+fn keyed(devices: &[DeviceInfo]) -> Vec<(DeviceKey, DeviceInfo)> {
+    devices
+        .iter()
+        .map(|info| {
+            let key = DeviceKey {
+                bus: info.bus,
+                address: info.address,
+                vendor_id: 0x20a0,
+                product_id: 0x41e5,
+            };
+            (key, info.clone())
+        })
+        .collect()
+}
+
+#[test]
+fn polling_reports_attach_and_detach() {
+    let bus = Arc::new(Mutex::new(vec![info(Some("BS000001-3.0"), 4)]));
+    let enumerated = bus.clone();
+    let watcher = DeviceWatcher::polling_with(
+        Duration::from_millis(5),
+        move || Ok(keyed(&enumerated.lock().unwrap())),
+        |info: &DeviceInfo| Ok(info.clone()),
+    );
+    let timeout = Duration::from_secs(2);
+
+    assert_eq!(
+        watcher.recv_timeout(timeout),
+        Some(DeviceEvent::Attached(info(Some("BS000001-3.0"), 4)))
+    );
+
+    bus.lock().unwrap().push(info(None, 9));
+    assert_eq!(
+        watcher.recv_timeout(timeout),
+        Some(DeviceEvent::Attached(info(None, 9)))
+    );
+
+    bus.lock().unwrap().clear();
+    let mut detached = vec![
+        watcher.recv_timeout(timeout).unwrap(),
+        watcher.recv_timeout(timeout).unwrap(),
+    ];
+    detached.sort_by_key(|event| format!("{:?}", event));
+
+    assert_eq!(
+        detached,
+        vec![
+            DeviceEvent::Detached("1-9".to_string()),
+            DeviceEvent::Detached("BS000001-3.0".to_string()),
+        ]
+    );
+    assert_eq!(watcher.try_recv(), None);
+}
+
+#[test]
+fn polling_describes_each_device_once() {
+    let bus = Arc::new(Mutex::new(vec![info(Some("BS000001-3.0"), 4)]));
+    let enumerated = bus.clone();
+    let described = Arc::new(AtomicUsize::new(0));
+    let counter = described.clone();
+    let watcher = DeviceWatcher::polling_with(
+        Duration::from_millis(5),
+        move || Ok(keyed(&enumerated.lock().unwrap())),
+        move |info: &DeviceInfo| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(info.clone())
+        },
+    );
+    let timeout = Duration::from_secs(2);
+
+    assert!(matches!(
+        watcher.recv_timeout(timeout),
+        Some(DeviceEvent::Attached(_))
+    ));
+
+    bus.lock().unwrap().push(info(Some("BS000002-3.0"), 5));
+    assert!(matches!(
+        watcher.recv_timeout(timeout),
+        Some(DeviceEvent::Attached(_))
+    ));
+
+    // Let several more rounds see the same two devices
+    std::thread::sleep(Duration::from_millis(50));
+
+    assert_eq!(described.load(Ordering::SeqCst), 2);
+    assert_eq!(watcher.try_recv(), None);
+}